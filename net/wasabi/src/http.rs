@@ -1,23 +1,375 @@
 extern crate alloc;
+use alloc::collections::BTreeMap;
 use alloc::format;
+use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
+use core::cell::RefCell;
 use noli::net::lookup_host;
 use noli::net::SocketAddr;
 use noli::net::TcpStream;
+use saba_core::auth::parse_challenges;
+use saba_core::auth::select_challenge;
+use saba_core::auth::AuthCache;
+use saba_core::auth::Credentials;
+use saba_core::cache::HttpCache;
+use saba_core::cookie::CookieJar;
 use saba_core::error::Error;
+use saba_core::http::response_length;
+use saba_core::http::Headers;
+use saba_core::http::HttpClientConfig;
+use saba_core::http::HttpRequest;
 use saba_core::http::HttpResponse;
+use saba_core::url::Url;
 
-pub struct HttpClient {}
+// https://fetch.spec.whatwg.org/#http-redirect-fetch
+const DEFAULT_MAX_REDIRECTS: usize = 20;
+
+/// 保存していた認証情報が拒否されたときに、もう一度だけ入力を求めるための上限
+const MAX_AUTH_RETRIES: usize = 2;
+
+pub struct HttpClient {
+    config: HttpClientConfig,
+    oom_handler: Option<fn(usize)>,
+    max_redirects: usize,
+    cookie_jar: Option<Rc<RefCell<CookieJar>>>,
+    cache: Option<Rc<RefCell<HttpCache>>>,
+    clock: Option<fn() -> i64>,
+    random: Option<fn() -> u32>,
+    credentials_handler: Option<fn(&Url, &str) -> Option<Credentials>>,
+    auth_cache: RefCell<AuthCache>,
+    /// keep-alive で使い回す接続。"host:port" をキーにして、ホストごとに 1 本だけ保持する
+    connections: RefCell<BTreeMap<String, TcpStream>>,
+}
 
 impl HttpClient {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            config: HttpClientConfig::new(),
+            oom_handler: None,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            cookie_jar: None,
+            cache: None,
+            clock: None,
+            random: None,
+            credentials_handler: None,
+            auth_cache: RefCell::new(AuthCache::new()),
+            connections: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// User-Agent などの全てのリクエストに付けるヘッダを設定する
+    pub fn set_config(&mut self, config: HttpClientConfig) {
+        self.config = config;
+    }
+
+    /// タブ間でクッキーを共有できるように、CookieJar は呼び出し側が持つ
+    pub fn set_cookie_jar(&mut self, cookie_jar: Rc<RefCell<CookieJar>>) {
+        self.cookie_jar = Some(cookie_jar);
+    }
+
+    pub fn set_cache(&mut self, cache: Rc<RefCell<HttpCache>>) {
+        self.cache = Some(cache);
+    }
+
+    /// クッキーやキャッシュの有効期限の判定に使う現在時刻 (Unix 時間、秒) を返す関数を設定する。
+    /// 設定されていない場合は 0 として扱う
+    pub fn set_clock(&mut self, clock: fn() -> i64) {
+        self.clock = Some(clock);
+    }
+
+    fn now(&self) -> i64 {
+        match self.clock {
+            Some(clock) => clock(),
+            None => 0,
+        }
+    }
+
+    /// Digest 認証の cnonce に使う、予測できない乱数を返す関数を設定する。
+    /// noli には乱数源がないため、設定されていない場合は Digest 認証を使わない
+    pub fn set_random(&mut self, random: fn() -> u32) {
+        self.random = Some(random);
+    }
+
+    pub fn set_max_redirects(&mut self, max_redirects: usize) {
+        self.max_redirects = max_redirects;
+    }
+
+    /// レスポンスバッファの確保に失敗したときに、要求したバイト数を引数に呼ばれる
+    pub fn set_oom_handler(&mut self, handler: fn(usize)) {
+        self.oom_handler = Some(handler);
+    }
+
+    /// 401 Unauthorized を受け取ったときに、URL とレルムを引数に呼ばれる。
+    /// None を返すと認証せずに 401 のレスポンスを返す
+    pub fn set_credentials_handler(&mut self, handler: fn(&Url, &str) -> Option<Credentials>) {
+        self.credentials_handler = Some(handler);
+    }
+
+    /// 保持している keep-alive の接続をすべて閉じる
+    pub fn close_connections(&self) {
+        self.connections.borrow_mut().clear();
     }
 
     pub fn get(&self, host: String, port: u16, path: String) -> Result<HttpResponse, Error> {
-        let ips = match lookup_host(&host) {
+        self.request("GET", host, port, path, None)
+    }
+
+    pub fn post(
+        &self,
+        host: String,
+        port: u16,
+        path: String,
+        content_type: String,
+        body: String,
+    ) -> Result<HttpResponse, Error> {
+        self.request("POST", host, port, path, Some((content_type, body)))
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        host: String,
+        port: u16,
+        path: String,
+        body: Option<(String, String)>,
+    ) -> Result<HttpResponse, Error> {
+        let mut method = method;
+        let mut body = body;
+        let mut url = Url::new(format!(
+            "http://{}:{}/{}",
+            host,
+            port,
+            path.trim_start_matches('/')
+        ))
+        .parse()?;
+
+        let mut redirects = 0;
+        let mut auth_retries = 0;
+        loop {
+            if url.scheme() != "http" {
+                return Err(Error::Network(format!(
+                    "Unsupported scheme: {}",
+                    url.scheme()
+                )));
+            }
+
+            let port = match url.port().parse::<u16>() {
+                Ok(port) => port,
+                Err(_) => return Err(Error::UrlParse(format!("Invalid port: {}", url.port()))),
+            };
+
+            let mut target = url.raw_path();
+            if !url.searchpart().is_empty() {
+                target.push('?');
+                target.push_str(&url.raw_searchpart());
+            }
+
+            let mut headers = Headers::new();
+            if let Some(jar) = &self.cookie_jar {
+                if let Some(cookie) = jar.borrow().cookie_header(&url, self.now()) {
+                    headers.append("Cookie", &cookie);
+                }
+            }
+
+            let cache = match &self.cache {
+                Some(cache) if method == "GET" => Some(cache),
+                _ => None,
+            };
+            if let Some(cache) = cache {
+                if let Some(mut response) = cache.borrow().fresh_response(&url, self.now()) {
+                    response.set_url(url);
+                    return Ok(response);
+                }
+                headers.extend(cache.borrow().validators(&url));
+            }
+
+            let authorization = self.auth_cache.borrow_mut().authorization(
+                &url,
+                method,
+                &format!("/{}", target),
+                self.random,
+            );
+            if let Some(authorization) = &authorization {
+                headers.append("Authorization", authorization);
+            }
+
+            let mut response =
+                self.send(method, &url.host(), port, &target, &headers, body.as_ref())?;
+
+            if let Some(jar) = &self.cookie_jar {
+                for header in response.headers() {
+                    if header.name().eq_ignore_ascii_case("Set-Cookie") {
+                        jar.borrow_mut().store(&url, &header.value(), self.now());
+                    }
+                }
+            }
+
+            // https://datatracker.ietf.org/doc/html/rfc9111#section-4.4
+            //
+            // POST などが成功した場合は、同じ URL のキャッシュを無効にする
+            if let Some(cache) = &self.cache {
+                if !matches!(method, "GET" | "HEAD") && (200..400).contains(&response.status_code())
+                {
+                    cache.borrow_mut().remove(&url);
+                }
+            }
+
+            if let Some(cache) = cache {
+                if response.status_code() == 304 {
+                    if let Some(cached) = cache.borrow_mut().revalidate(&url, &response, self.now())
+                    {
+                        response = cached;
+                    }
+                } else {
+                    cache.borrow_mut().store(&url, &response, self.now());
+                }
+            }
+
+            if response.status_code() == 401
+                && auth_retries < MAX_AUTH_RETRIES
+                && self.authenticate(&url, &response, authorization.is_some())
+            {
+                auth_retries += 1;
+                continue;
+            }
+
+            match response.redirect_location() {
+                Some(location) => {
+                    if redirects >= self.max_redirects {
+                        return Err(Error::Network(format!(
+                            "Too many redirects (max {})",
+                            self.max_redirects
+                        )));
+                    }
+                    redirects += 1;
+                    auth_retries = 0;
+
+                    // 307/308 以外のリダイレクトでは GET に切り替えてボディを送らない
+                    if !matches!(response.status_code(), 307 | 308) {
+                        method = "GET";
+                        body = None;
+                    }
+                    url = url.join(&location)?;
+                }
+                None => {
+                    response.set_url(url);
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    /// https://datatracker.ietf.org/doc/html/rfc9110#section-11.6.1
+    ///
+    /// 401 のレスポンスのチャレンジに応じて認証情報を用意し、リクエストをやり直すかどうかを返す。
+    /// `rejected` はこのリクエストで認証情報を送っていた (そして拒否された) かどうか
+    fn authenticate(&self, url: &Url, response: &HttpResponse, rejected: bool) -> bool {
+        let mut challenges = Vec::new();
+        for value in response.headers().get_all("WWW-Authenticate") {
+            challenges.extend(parse_challenges(&value));
+        }
+        if self.random.is_none() {
+            challenges.retain(|c| !c.scheme().eq_ignore_ascii_case("Digest"));
+        }
+        let challenge = match select_challenge(&challenges) {
+            Some(challenge) => challenge,
+            None => return false,
+        };
+        let realm = challenge.realm();
+
+        let mut auth_cache = self.auth_cache.borrow_mut();
+        if let Some(credentials) = auth_cache.credentials(url, &realm) {
+            // Digest の nonce が古くなっただけなら、同じ認証情報を新しい nonce で送り直す
+            let stale = challenge
+                .param("stale")
+                .is_some_and(|s| s.eq_ignore_ascii_case("true"));
+            if !rejected || stale {
+                auth_cache.store(url, credentials, challenge);
+                return true;
+            }
+            auth_cache.remove(url, &realm);
+        }
+
+        let handler = match self.credentials_handler {
+            Some(handler) => handler,
+            None => return false,
+        };
+        match handler(url, &realm) {
+            Some(credentials) => {
+                auth_cache.store(url, credentials, challenge);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        host: &str,
+        port: u16,
+        path: &str,
+        headers: &Headers,
+        body: Option<&(String, String)>,
+    ) -> Result<HttpResponse, Error> {
+        let mut request = HttpRequest::new(method, path);
+        request.headers_mut().append("Host", host);
+        self.config.apply(&mut request);
+        for h in headers {
+            request.headers_mut().set(&h.name(), &h.value());
+        }
+        if let Some((content_type, body)) = body {
+            request.set_body(content_type, body.clone());
+        }
+        let request = request.serialize();
+
+        let key = format!("{}:{}", host, port);
+        // 使い回した接続はサーバー側で既に閉じられていることがあるので、失敗したら繋ぎ直す。
+        // POST などの冪等でないリクエストは送り直せないので、最初から新しい接続で送る
+        // https://datatracker.ietf.org/doc/html/rfc9110#section-9.2.2
+        let pooled = if matches!(method, "GET" | "HEAD") {
+            self.connections.borrow_mut().remove(&key)
+        } else {
+            None
+        };
+        let mut exchanged = None;
+        if let Some(mut stream) = pooled {
+            if let Some((received, reusable)) = self.exchange(&mut stream, &request)? {
+                exchanged = Some((received, reusable, stream));
+            }
+        }
+        let (received, reusable, stream) = match exchanged {
+            Some(exchanged) => exchanged,
+            None => {
+                let mut stream = Self::connect(host, port)?;
+                match self.exchange(&mut stream, &request)? {
+                    Some((received, reusable)) => (received, reusable, stream),
+                    None => {
+                        return Err(Error::Network(
+                            "Failed to receive a response from TCP stream".to_string(),
+                        ))
+                    }
+                }
+            }
+        };
+
+        let response = HttpResponse::from_bytes(&received)?;
+        let close = response.headers().iter().any(|h| {
+            h.name().eq_ignore_ascii_case("Connection")
+                && h.value()
+                    .split(',')
+                    .any(|v| v.trim().eq_ignore_ascii_case("close"))
+        });
+        if reusable && !close {
+            self.connections.borrow_mut().insert(key, stream);
+        }
+        Ok(response)
+    }
+
+    pub(crate) fn connect(host: &str, port: u16) -> Result<TcpStream, Error> {
+        let ips = match lookup_host(host) {
             Ok(ips) => ips,
             Err(e) => {
                 return Err(Error::Network(format!(
@@ -31,43 +383,46 @@ impl HttpClient {
             return Err(Error::Network("Failed to find IP addresses".to_string()));
         }
 
-        let socket_addr: SocketAddr = (ips[0], port).into();
-
-        let mut stream = match TcpStream::connect(socket_addr) {
-            Ok(stream) => stream,
-            Err(_) => {
-                return Err(Error::Network(
-                    "Failed to connect to TCP stream".to_string(),
-                ))
+        // 名前解決で複数のアドレスが返ってきた場合は、接続できるまで順番に試す。
+        // noli の TcpStream::connect はブロックするので、並行して試すことはできない
+        for ip in ips {
+            let socket_addr: SocketAddr = (ip, port).into();
+            if let Ok(stream) = TcpStream::connect(socket_addr) {
+                return Ok(stream);
             }
-        };
+        }
 
-        let mut request = String::from("GET /");
-        request.push_str(&path);
-        request.push_str(" HTTP/1.1\n");
-
-        // ヘッダの追加
-        request.push_str("Host: ");
-        request.push_str(&host);
-        request.push('\n');
-        request.push_str("Accept: text/html\n");
-        request.push_str("Connection: close\n");
-        request.push('\n');
-
-        let _bytes_written = match stream.write(request.as_bytes()) {
-            Ok(bytes) => bytes,
-            Err(_) => {
-                return Err(Error::Network(
-                    "Failed to send a request to TCP stream".to_string(),
-                ))
-            }
-        };
+        Err(Error::Network(
+            "Failed to connect to TCP stream".to_string(),
+        ))
+    }
+
+    /// リクエストを送ってレスポンスを受信する。レスポンスを受け取る前に接続が閉じられた場合は
+    /// None を返す。レスポンスの終わりが分かり、接続を使い回せるかどうかも合わせて返す
+    fn exchange(
+        &self,
+        stream: &mut TcpStream,
+        request: &str,
+    ) -> Result<Option<(Vec<u8>, bool)>, Error> {
+        if stream.write(request.as_bytes()).is_err() {
+            return Ok(None);
+        }
 
         let mut received = Vec::new();
         loop {
+            if let Some(len) = response_length(&received) {
+                // 続けて送られてきたバイトがある場合は、区切りが分からなくなるので使い回さない
+                if len == received.len() {
+                    return Ok(Some((received, true)));
+                }
+                received.truncate(len);
+                return Ok(Some((received, false)));
+            }
+
             let mut buf = [0u8; 4096];
             let bytes_read = match stream.read(&mut buf) {
                 Ok(bytes) => bytes,
+                Err(_) if received.is_empty() => return Ok(None),
                 Err(_) => {
                     return Err(Error::Network(
                         "Failed to receive a request from TCP stream".to_string(),
@@ -77,12 +432,21 @@ impl HttpClient {
             if bytes_read == 0 {
                 break;
             }
+            if received.try_reserve(bytes_read).is_err() {
+                if let Some(handler) = self.oom_handler {
+                    handler(received.len() + bytes_read);
+                }
+                return Err(Error::OutOfMemory(format!(
+                    "Failed to allocate {} bytes for the response",
+                    received.len() + bytes_read
+                )));
+            }
             received.extend_from_slice(&buf[..bytes_read]);
         }
 
-        match core::str::from_utf8(&received) {
-            Ok(response) => HttpResponse::new(response.to_string()),
-            Err(e) => Err(Error::Network(format!("Invalid received response: {}", e))),
+        if received.is_empty() {
+            return Ok(None);
         }
+        Ok(Some((received, false)))
     }
 }
@@ -1,3 +1,4 @@
 #![no_std]
 
 pub mod http;
+pub mod websocket;
@@ -0,0 +1,226 @@
+extern crate alloc;
+use crate::http::HttpClient;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use noli::net::TcpStream;
+use saba_core::base64;
+use saba_core::error::Error;
+use saba_core::http::response_length;
+use saba_core::http::HttpResponse;
+use saba_core::websocket::handshake_request;
+use saba_core::websocket::verify_handshake;
+use saba_core::websocket::Frame;
+use saba_core::websocket::Opcode;
+
+/// 受信するメッセージの大きさの上限。分割されたメッセージは連結した後の大きさで数える
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// フレームのヘッダの最大の長さ
+const MAX_FRAME_HEADER_SIZE: usize = 14;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    /// サーバーから受け取った Close フレームのステータスコードと理由
+    Close(Option<(u16, String)>),
+}
+
+pub struct WebSocket {
+    stream: TcpStream,
+    received: Vec<u8>,
+    /// Sec-WebSocket-Key とマスクを作るための乱数を返す関数
+    random: fn() -> u32,
+    closed: bool,
+}
+
+impl WebSocket {
+    /// https://datatracker.ietf.org/doc/html/rfc6455#section-4.1
+    ///
+    /// noli には乱数源がないため、マスクに使う予測できない乱数を返す関数を呼び出し側が渡す
+    /// https://datatracker.ietf.org/doc/html/rfc6455#section-5.3
+    pub fn connect(
+        host: String,
+        port: u16,
+        path: String,
+        random: fn() -> u32,
+    ) -> Result<Self, Error> {
+        let stream = HttpClient::connect(&host, port)?;
+        let mut ws = Self {
+            stream,
+            received: Vec::new(),
+            random,
+            closed: false,
+        };
+
+        let mut key = [0u8; 16];
+        for chunk in key.chunks_mut(4) {
+            chunk.copy_from_slice(&(ws.random)().to_le_bytes());
+        }
+        let key = base64::encode(&key);
+
+        let request = handshake_request(&host, &path, &key);
+        ws.write(request.serialize().as_bytes())?;
+
+        // ハンドシェイクのレスポンスの直後にフレームが続いていることがあるので、残りは取っておく
+        let len = loop {
+            if let Some(len) = response_length(&ws.received) {
+                break len;
+            }
+            ws.read_more()?;
+        };
+        let response = HttpResponse::from_bytes(&ws.received[..len])?;
+        ws.received.drain(..len);
+        verify_handshake(&response, &key)?;
+
+        Ok(ws)
+    }
+
+    pub fn send_text(&mut self, text: &str) -> Result<(), Error> {
+        self.send_frame(Frame::new(true, Opcode::Text, text.as_bytes().to_vec()))
+    }
+
+    pub fn send_binary(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.send_frame(Frame::new(true, Opcode::Binary, data.to_vec()))
+    }
+
+    /// 次のメッセージを受信するまでブロックする。Ping には自動で Pong を返し、
+    /// 分割されたメッセージは連結してから返す
+    pub fn receive(&mut self) -> Result<Message, Error> {
+        let mut message: Option<(Opcode, Vec<u8>)> = None;
+        loop {
+            let frame = self.read_frame()?;
+            match frame.opcode() {
+                Opcode::Ping => {
+                    self.send_frame(Frame::new(true, Opcode::Pong, frame.payload()))?;
+                    continue;
+                }
+                Opcode::Pong => continue,
+                Opcode::Close => {
+                    // https://datatracker.ietf.org/doc/html/rfc6455#section-5.5.1
+                    if !self.closed {
+                        let code = frame.close_reason().map(|(code, _)| code).unwrap_or(1000);
+                        self.send_frame(Frame::close(code, ""))?;
+                        self.closed = true;
+                    }
+                    return Ok(Message::Close(frame.close_reason()));
+                }
+                Opcode::Continuation => match &mut message {
+                    Some((_, data)) => {
+                        let payload = frame.payload();
+                        reserve(data, payload.len(), MAX_MESSAGE_SIZE)?;
+                        data.extend(payload);
+                    }
+                    None => {
+                        return Err(Error::Network(
+                            "Unexpected WebSocket continuation frame".to_string(),
+                        ))
+                    }
+                },
+                Opcode::Text | Opcode::Binary => {
+                    if message.is_some() {
+                        return Err(Error::Network(
+                            "WebSocket message was interrupted by another message".to_string(),
+                        ));
+                    }
+                    message = Some((frame.opcode(), frame.payload()));
+                }
+            }
+
+            if !frame.fin() {
+                continue;
+            }
+            if let Some((opcode, data)) = message.take() {
+                if opcode == Opcode::Binary {
+                    return Ok(Message::Binary(data));
+                }
+                return match String::from_utf8(data) {
+                    Ok(text) => Ok(Message::Text(text)),
+                    Err(e) => Err(Error::Network(format!(
+                        "Invalid WebSocket text message: {}",
+                        e
+                    ))),
+                };
+            }
+        }
+    }
+
+    /// Close フレームを送り、サーバーから Close フレームが返ってくるまで待つ
+    pub fn close(&mut self, code: u16, reason: &str) -> Result<(), Error> {
+        if self.closed {
+            return Ok(());
+        }
+        self.send_frame(Frame::close(code, reason))?;
+        self.closed = true;
+
+        loop {
+            match self.read_frame() {
+                Ok(frame) if frame.opcode() == Opcode::Close => return Ok(()),
+                Ok(_) => continue,
+                // 接続が閉じられた場合も閉じたものとして扱う
+                Err(_) => return Ok(()),
+            }
+        }
+    }
+
+    fn send_frame(&mut self, frame: Frame) -> Result<(), Error> {
+        if self.closed {
+            return Err(Error::Network("WebSocket is already closed".to_string()));
+        }
+        let mask = (self.random)().to_le_bytes();
+        self.write(&frame.encode(mask))
+    }
+
+    fn read_frame(&mut self) -> Result<Frame, Error> {
+        loop {
+            if let Some((frame, len)) = Frame::decode(&self.received)? {
+                self.received.drain(..len);
+                return Ok(frame);
+            }
+            self.read_more()?;
+        }
+    }
+
+    fn read_more(&mut self) -> Result<(), Error> {
+        let mut buf = [0u8; 4096];
+        let bytes_read = match self.stream.read(&mut buf) {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                return Err(Error::Network(
+                    "Failed to receive from TCP stream".to_string(),
+                ))
+            }
+        };
+        if bytes_read == 0 {
+            return Err(Error::Network("WebSocket connection closed".to_string()));
+        }
+        // 巨大な長さを宣言したフレームや終わらないハンドシェイクのレスポンスを際限なく溜めない
+        reserve(
+            &mut self.received,
+            bytes_read,
+            MAX_MESSAGE_SIZE + MAX_FRAME_HEADER_SIZE,
+        )?;
+        self.received.extend_from_slice(&buf[..bytes_read]);
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        match self.stream.write(bytes) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Error::Network("Failed to send to TCP stream".to_string())),
+        }
+    }
+}
+
+fn reserve(buf: &mut Vec<u8>, additional: usize, limit: usize) -> Result<(), Error> {
+    let size = buf.len().saturating_add(additional);
+    if size > limit || buf.try_reserve(additional).is_err() {
+        return Err(Error::OutOfMemory(format!(
+            "Failed to allocate {} bytes for the WebSocket message",
+            size
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,672 @@
+//! https://datatracker.ietf.org/doc/html/rfc9110#section-11
+//! https://datatracker.ietf.org/doc/html/rfc7617
+//! https://datatracker.ietf.org/doc/html/rfc7616
+
+use crate::base64;
+use crate::url::Url;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    username: String,
+    password: String,
+}
+
+impl Credentials {
+    pub fn new(username: String, password: String) -> Self {
+        Self { username, password }
+    }
+
+    pub fn username(&self) -> String {
+        self.username.clone()
+    }
+
+    pub fn password(&self) -> String {
+        self.password.clone()
+    }
+}
+
+/// WWW-Authenticate ヘッダの 1 つのチャレンジ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    scheme: String,
+    params: Vec<(String, String)>,
+    token68: Option<String>,
+}
+
+impl Challenge {
+    pub fn scheme(&self) -> String {
+        self.scheme.clone()
+    }
+
+    /// パラメータ名は大文字小文字を区別しない
+    pub fn param(&self, name: &str) -> Option<String> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    }
+
+    /// Negotiate などのスキームで、パラメータの代わりに送られる値
+    pub fn token68(&self) -> Option<String> {
+        self.token68.clone()
+    }
+
+    pub fn realm(&self) -> String {
+        self.param("realm").unwrap_or_default()
+    }
+
+    fn is_basic(&self) -> bool {
+        self.scheme.eq_ignore_ascii_case("Basic")
+    }
+
+    /// MD5 と MD5-sess、qop=auth (または qop なし) の Digest にだけ対応する
+    fn is_supported_digest(&self) -> bool {
+        if !self.scheme.eq_ignore_ascii_case("Digest") || self.param("nonce").is_none() {
+            return false;
+        }
+        let algorithm_supported = match self.param("algorithm") {
+            Some(a) => a.eq_ignore_ascii_case("MD5") || a.eq_ignore_ascii_case("MD5-sess"),
+            None => true,
+        };
+        let qop_supported = match self.param("qop") {
+            Some(qop) => qop
+                .split(',')
+                .any(|q| q.trim().eq_ignore_ascii_case("auth")),
+            None => true,
+        };
+        algorithm_supported && qop_supported
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc9110#section-11.6.1
+///
+/// 1 つのヘッダに複数のチャレンジがカンマ区切りで並んでいることもある
+pub fn parse_challenges(value: &str) -> Vec<Challenge> {
+    let bytes = value.as_bytes();
+    let mut challenges: Vec<Challenge> = Vec::new();
+    let mut pos = 0;
+    loop {
+        pos = skip_while(bytes, pos, |b| b == b',' || is_ows(b));
+        if pos >= bytes.len() {
+            break;
+        }
+
+        let (token, after) = read_while(bytes, pos, is_tchar);
+        if token.is_empty() {
+            // 解釈できない部分は次のカンマまで読み飛ばす
+            pos = skip_while(bytes, pos + 1, |b| b != b',');
+            continue;
+        }
+
+        // "=" が続かないトークンは新しいチャレンジのスキーム
+        let eq = skip_while(bytes, after, is_ows);
+        if bytes.get(eq) != Some(&b'=') {
+            pos = skip_while(bytes, after, is_ows);
+            let token68 = match read_token68(bytes, pos) {
+                Some((token68, end)) => {
+                    pos = end;
+                    Some(token68)
+                }
+                None => None,
+            };
+            challenges.push(Challenge {
+                scheme: token,
+                params: Vec::new(),
+                token68,
+            });
+            continue;
+        }
+
+        // "=" の前後には空白 (BWS) があってもよい
+        let value_start = skip_while(bytes, eq + 1, is_ows);
+        let (value, end) = if bytes.get(value_start) == Some(&b'"') {
+            read_quoted_string(bytes, value_start)
+        } else {
+            read_while(bytes, value_start, is_tchar)
+        };
+        if let Some(challenge) = challenges.last_mut() {
+            challenge.params.push((token, value));
+        }
+        pos = skip_while(bytes, end, |b| b != b',');
+    }
+    challenges
+}
+
+/// 対応しているチャレンジのうち、Basic よりも Digest を優先して選ぶ
+pub fn select_challenge(challenges: &[Challenge]) -> Option<Challenge> {
+    challenges
+        .iter()
+        .find(|c| c.is_supported_digest())
+        .or_else(|| challenges.iter().find(|c| c.is_basic()))
+        .cloned()
+}
+
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+fn is_ows(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+fn skip_while(bytes: &[u8], pos: usize, f: impl Fn(u8) -> bool) -> usize {
+    let mut pos = pos;
+    while pos < bytes.len() && f(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+fn read_while(bytes: &[u8], pos: usize, f: impl Fn(u8) -> bool) -> (String, usize) {
+    let end = skip_while(bytes, pos, f);
+    (String::from_utf8_lossy(&bytes[pos..end]).into_owned(), end)
+}
+
+/// token68 の後にはカンマか値の終わりが続く。そうでなければ auth-param の名前として扱う
+fn read_token68(bytes: &[u8], pos: usize) -> Option<(String, usize)> {
+    let chars_end = skip_while(bytes, pos, |b| {
+        b.is_ascii_alphanumeric() || b"-._~+/".contains(&b)
+    });
+    if chars_end == pos {
+        return None;
+    }
+    let end = skip_while(bytes, chars_end, |b| b == b'=');
+    let next = skip_while(bytes, end, is_ows);
+    if next < bytes.len() && bytes[next] != b',' {
+        return None;
+    }
+    Some((String::from_utf8_lossy(&bytes[pos..end]).into_owned(), end))
+}
+
+/// `pos` の '"' から始まる quoted-string を読み、エスケープを外した値を返す
+fn read_quoted_string(bytes: &[u8], pos: usize) -> (String, usize) {
+    let mut value = Vec::new();
+    let mut i = pos + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => return (String::from_utf8_lossy(&value).into_owned(), i + 1),
+            b'\\' if i + 1 < bytes.len() => {
+                value.push(bytes[i + 1]);
+                i += 2;
+            }
+            b => {
+                value.push(b);
+                i += 1;
+            }
+        }
+    }
+    (String::from_utf8_lossy(&value).into_owned(), i)
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc7617#section-2
+pub fn basic_authorization(credentials: &Credentials) -> String {
+    let user_pass = format!("{}:{}", credentials.username, credentials.password);
+    format!("Basic {}", base64::encode(user_pass.as_bytes()))
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc7616#section-3.4
+///
+/// `nonce_count` は同じ nonce を使うたびに 1 ずつ増やす
+pub fn digest_authorization(
+    credentials: &Credentials,
+    challenge: &Challenge,
+    method: &str,
+    uri: &str,
+    nonce_count: u32,
+    cnonce: &str,
+) -> Option<String> {
+    if !challenge.is_supported_digest() {
+        return None;
+    }
+
+    let realm = challenge.realm();
+    let nonce = challenge.param("nonce")?;
+    let algorithm = challenge.param("algorithm");
+    let qop = challenge.param("qop").map(|_| "auth");
+    let nc = format!("{:08x}", nonce_count);
+
+    let mut ha1 = md5_hex(
+        format!(
+            "{}:{}:{}",
+            credentials.username, realm, credentials.password
+        )
+        .as_bytes(),
+    );
+    if matches!(&algorithm, Some(a) if a.eq_ignore_ascii_case("MD5-sess")) {
+        ha1 = md5_hex(format!("{}:{}:{}", ha1, nonce, cnonce).as_bytes());
+    }
+    let ha2 = md5_hex(format!("{}:{}", method, uri).as_bytes());
+    let response = match qop {
+        Some(qop) => {
+            md5_hex(format!("{}:{}:{}:{}:{}:{}", ha1, nonce, nc, cnonce, qop, ha2).as_bytes())
+        }
+        None => md5_hex(format!("{}:{}:{}", ha1, nonce, ha2).as_bytes()),
+    };
+
+    let mut header = format!(
+        "Digest username={}, realm={}, nonce={}, uri={}, response=\"{}\"",
+        quote(&credentials.username),
+        quote(&realm),
+        quote(&nonce),
+        quote(uri),
+        response
+    );
+    if let Some(algorithm) = algorithm {
+        header.push_str(&format!(", algorithm={}", algorithm));
+    }
+    if let Some(opaque) = challenge.param("opaque") {
+        header.push_str(&format!(", opaque={}", quote(&opaque)));
+    }
+    if let Some(qop) = qop {
+        header.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce));
+    }
+    Some(header)
+}
+
+#[derive(Debug, Clone)]
+struct AuthEntry {
+    /// scheme://host:port
+    origin: String,
+    realm: String,
+    /// チャレンジを受け取った URL のパスの最後の "/" までの部分。
+    /// この下のパスには 401 を受け取る前から認証情報を送る
+    /// https://datatracker.ietf.org/doc/html/rfc7617#section-2.2
+    directory: String,
+    credentials: Credentials,
+    challenge: Challenge,
+    nonce_count: u32,
+}
+
+/// 保護空間 (オリジンとレルム) ごとに認証情報を覚えておく
+#[derive(Debug, Clone, Default)]
+pub struct AuthCache {
+    entries: Vec<AuthEntry>,
+}
+
+impl AuthCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn credentials(&self, url: &Url, realm: &str) -> Option<Credentials> {
+        let origin = origin(url);
+        self.entries
+            .iter()
+            .find(|e| e.origin == origin && e.realm == realm)
+            .map(|e| e.credentials.clone())
+    }
+
+    /// 同じ保護空間の認証情報があれば置き換える
+    pub fn store(&mut self, url: &Url, credentials: Credentials, challenge: Challenge) {
+        let origin = origin(url);
+        let realm = challenge.realm();
+        let mut directory = directory(url);
+        // 同じ保護空間をより上のディレクトリで認証していれば、その範囲を保つ
+        if let Some(e) = self
+            .entries
+            .iter()
+            .find(|e| e.origin == origin && e.realm == realm)
+        {
+            if directory.starts_with(&e.directory) {
+                directory = e.directory.clone();
+            }
+        }
+        self.entries
+            .retain(|e| !(e.origin == origin && e.realm == realm));
+        self.entries.push(AuthEntry {
+            origin,
+            realm,
+            directory,
+            credentials,
+            challenge,
+            nonce_count: 0,
+        });
+    }
+
+    pub fn remove(&mut self, url: &Url, realm: &str) {
+        let origin = origin(url);
+        self.entries
+            .retain(|e| !(e.origin == origin && e.realm == realm));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// `url` を含む保護空間で最後に認証に使った情報から Authorization ヘッダの値を作る。
+    /// `uri` はリクエストターゲット。`random` は Digest の cnonce に使う予測できない乱数を返す関数で、
+    /// None のときは Digest の認証情報を使わない
+    pub fn authorization(
+        &mut self,
+        url: &Url,
+        method: &str,
+        uri: &str,
+        random: Option<fn() -> u32>,
+    ) -> Option<String> {
+        let origin = origin(url);
+        let path = format!("/{}", url.raw_path());
+        let entry = self
+            .entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.origin == origin && path.starts_with(&e.directory))?;
+
+        if entry.challenge.is_basic() {
+            return Some(basic_authorization(&entry.credentials));
+        }
+
+        let random = random?;
+        let mut cnonce = String::new();
+        for _ in 0..4 {
+            let _ = write!(cnonce, "{:08x}", random());
+        }
+        entry.nonce_count += 1;
+        digest_authorization(
+            &entry.credentials,
+            &entry.challenge,
+            method,
+            uri,
+            entry.nonce_count,
+            &cnonce,
+        )
+    }
+}
+
+fn origin(url: &Url) -> String {
+    format!("{}://{}:{}", url.scheme(), url.host(), url.port())
+}
+
+fn directory(url: &Url) -> String {
+    let path = format!("/{}", url.raw_path());
+    match path.rfind('/') {
+        Some(i) => String::from(&path[..=i]),
+        None => path,
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc1321
+fn md5(input: &[u8]) -> [u8; 16] {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5,
+        9, 14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10,
+        15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    const K: [u32; 64] = [
+        0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613,
+        0xfd469501, 0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193,
+        0xa679438e, 0x49b40821, 0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d,
+        0x02441453, 0xd8a1e681, 0xe7d3fbc8, 0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed,
+        0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a, 0xfffa3942, 0x8771f681, 0x6d9d6122,
+        0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70, 0x289b7ec6, 0xeaa127fa,
+        0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665, 0xf4292244,
+        0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+        0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb,
+        0xeb86d391,
+    ];
+
+    let mut message = input.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((input.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in message.chunks(64) {
+        let mut m = [0u32; 16];
+        for (i, word) in block.chunks(4).enumerate() {
+            m[i] = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = f.wrapping_add(a).wrapping_add(K[i]).wrapping_add(m[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(S[i]));
+        }
+
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+    }
+
+    let mut digest = [0u8; 16];
+    for (i, word) in state.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+fn md5_hex(input: &[u8]) -> String {
+    md5(input).iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{:02x}", b);
+        hex
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn url(s: &str) -> Url {
+        Url::new(s.to_string())
+            .parse()
+            .expect("failed to parse url")
+    }
+
+    #[test]
+    fn test_md5() {
+        assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            md5_hex(b"The quick brown fox jumps over the lazy dog"),
+            "9e107d9d372bb6826bd81d3542a419d6"
+        );
+    }
+
+    #[test]
+    fn test_parse_challenges() {
+        let challenges = parse_challenges(
+            "Newauth realm=\"apps\", type=1, title=\"Login to \\\"apps\\\"\", Basic realm=\"simple\"",
+        );
+        assert_eq!(challenges.len(), 2);
+        assert_eq!(challenges[0].scheme(), "Newauth");
+        assert_eq!(challenges[0].realm(), "apps");
+        assert_eq!(challenges[0].param("TYPE"), Some("1".to_string()));
+        assert_eq!(
+            challenges[0].param("title"),
+            Some("Login to \"apps\"".to_string())
+        );
+        assert_eq!(challenges[1].scheme(), "Basic");
+        assert_eq!(challenges[1].realm(), "simple");
+    }
+
+    #[test]
+    fn test_parse_challenges_whitespace_around_equals() {
+        let challenges = parse_challenges("Basic realm = \"a, b\" , charset = \"UTF-8\"");
+        assert_eq!(challenges.len(), 1);
+        assert_eq!(challenges[0].realm(), "a, b");
+        assert_eq!(challenges[0].param("charset"), Some("UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_parse_challenges_token68() {
+        let challenges = parse_challenges("Negotiate YWJj==, Basic realm=\"x\", Bearer a/b+c");
+        assert_eq!(challenges.len(), 3);
+        assert_eq!(challenges[0].scheme(), "Negotiate");
+        assert_eq!(challenges[0].token68(), Some("YWJj==".to_string()));
+        assert!(challenges[0].param("YWJj").is_none());
+        assert_eq!(challenges[1].token68(), None);
+        assert_eq!(challenges[1].realm(), "x");
+        assert_eq!(challenges[2].token68(), Some("a/b+c".to_string()));
+    }
+
+    #[test]
+    fn test_select_challenge() {
+        let challenges = parse_challenges(
+            "Basic realm=\"a\", Digest realm=\"b\", nonce=\"n\", algorithm=SHA-256",
+        );
+        assert_eq!(
+            select_challenge(&challenges).map(|c| c.realm()),
+            Some("a".to_string())
+        );
+
+        let challenges = parse_challenges("Basic realm=\"a\", Digest realm=\"b\", nonce=\"n\"");
+        assert_eq!(
+            select_challenge(&challenges).map(|c| c.realm()),
+            Some("b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_basic_authorization() {
+        let credentials = Credentials::new("Aladdin".to_string(), "open sesame".to_string());
+        assert_eq!(
+            basic_authorization(&credentials),
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+    }
+
+    #[test]
+    fn test_digest_authorization() {
+        // https://datatracker.ietf.org/doc/html/rfc2617#section-3.5
+        let challenges = parse_challenges(
+            "Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+        );
+        let credentials = Credentials::new("Mufasa".to_string(), "Circle Of Life".to_string());
+        let header = digest_authorization(
+            &credentials,
+            &challenges[0],
+            "GET",
+            "/dir/index.html",
+            1,
+            "0a4f113b",
+        )
+        .expect("failed to build digest authorization");
+
+        assert_eq!(
+            header,
+            "Digest username=\"Mufasa\", realm=\"testrealm@host.com\", nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", uri=\"/dir/index.html\", response=\"6629fae49393a05397450978507c4ef1\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\", qop=auth, nc=00000001, cnonce=\"0a4f113b\""
+        );
+    }
+
+    #[test]
+    fn test_auth_cache() {
+        let mut cache = AuthCache::new();
+        let u = url("http://example.com/private/");
+        assert!(cache.authorization(&u, "GET", "/private/", None).is_none());
+
+        let challenge = parse_challenges("Basic realm=\"private\"").remove(0);
+        let credentials = Credentials::new("user".to_string(), "pass".to_string());
+        cache.store(&u, credentials.clone(), challenge);
+
+        assert_eq!(cache.credentials(&u, "private"), Some(credentials));
+        assert!(cache.credentials(&u, "other").is_none());
+        assert!(cache
+            .credentials(&url("http://example.com:8080/"), "private")
+            .is_none());
+        assert_eq!(
+            cache.authorization(
+                &url("http://example.com/private/a/b"),
+                "GET",
+                "/private/a/b",
+                None
+            ),
+            Some("Basic dXNlcjpwYXNz".to_string())
+        );
+
+        cache.remove(&u, "private");
+        assert!(cache.authorization(&u, "GET", "/private/", None).is_none());
+    }
+
+    #[test]
+    fn test_auth_cache_path_scope() {
+        let mut cache = AuthCache::new();
+        let challenge = parse_challenges("Basic realm=\"r\"").remove(0);
+        let credentials = Credentials::new("user".to_string(), "pass".to_string());
+        cache.store(
+            &url("http://example.com/docs/index.html"),
+            credentials.clone(),
+            challenge.clone(),
+        );
+
+        assert!(cache
+            .authorization(
+                &url("http://example.com/docs/a.html"),
+                "GET",
+                "/docs/a.html",
+                None
+            )
+            .is_some());
+        assert!(cache
+            .authorization(&url("http://example.com/other"), "GET", "/other", None)
+            .is_none());
+        assert!(cache
+            .authorization(&url("http://example.com/docsx"), "GET", "/docsx", None)
+            .is_none());
+
+        // より深いパスで認証し直しても、上のディレクトリの範囲は保つ
+        cache.store(
+            &url("http://example.com/docs/sub/page"),
+            credentials,
+            challenge,
+        );
+        assert!(cache
+            .authorization(
+                &url("http://example.com/docs/a.html"),
+                "GET",
+                "/docs/a.html",
+                None
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn test_auth_cache_digest_nonce_count() {
+        fn random() -> u32 {
+            0x12345678
+        }
+
+        let mut cache = AuthCache::new();
+        let u = url("http://example.com/");
+        let challenge =
+            parse_challenges("Digest realm=\"r\", nonce=\"abc\", qop=\"auth\"").remove(0);
+        cache.store(
+            &u,
+            Credentials::new("user".to_string(), "pass".to_string()),
+            challenge,
+        );
+
+        // 乱数がなければ、予測できる cnonce を送らないように Digest の認証情報を使わない
+        assert!(cache.authorization(&u, "GET", "/", None).is_none());
+
+        let first = cache
+            .authorization(&u, "GET", "/", Some(random))
+            .expect("no authorization");
+        let second = cache
+            .authorization(&u, "GET", "/", Some(random))
+            .expect("no authorization");
+        assert!(first.contains("nc=00000001"));
+        assert!(second.contains("nc=00000002"));
+        assert!(first.contains("cnonce=\"12345678123456781234567812345678\""));
+    }
+}
@@ -0,0 +1,776 @@
+//! https://datatracker.ietf.org/doc/html/rfc7932
+
+mod tables;
+
+use crate::error::Error;
+use crate::inflate::reserve;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use tables::{SIGNED_LUT, TRANSFORMS, UTF8_LUT0, UTF8_LUT1};
+
+const MAX_BITS: usize = 15;
+const NUM_LITERAL_CODES: usize = 256;
+const NUM_COMMAND_CODES: usize = 704;
+const NUM_BLOCK_COUNT_CODES: usize = 26;
+
+/// https://datatracker.ietf.org/doc/html/rfc7932#appendix-A
+static DICTIONARY: &[u8; 122784] = include_bytes!("brotli/dictionary.bin");
+const DICTIONARY_OFFSETS: [usize; 25] = [
+    0, 0, 0, 0, 0, 4096, 9216, 21504, 35840, 44032, 53248, 63488, 74752, 87040, 93696, 100864,
+    104704, 106752, 108928, 113536, 115968, 118528, 119872, 121280, 122016,
+];
+const DICTIONARY_SIZE_BITS: [u32; 25] = [
+    0, 0, 0, 0, 10, 10, 11, 11, 10, 10, 10, 10, 10, 9, 9, 8, 7, 7, 8, 7, 7, 6, 6, 5, 5,
+];
+
+const CODE_LENGTH_CODE_ORDER: [usize; 18] =
+    [1, 2, 3, 4, 0, 5, 17, 6, 16, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+const BLOCK_COUNT_BASE: [usize; 26] = [
+    1, 5, 9, 13, 17, 25, 33, 41, 49, 65, 81, 97, 113, 145, 177, 209, 241, 305, 369, 497, 753, 1265,
+    2289, 4337, 8433, 16625,
+];
+const BLOCK_COUNT_EXTRA: [u32; 26] = [
+    2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 6, 6, 7, 8, 9, 10, 11, 12, 13, 24,
+];
+
+const INSERT_LENGTH_BASE: [usize; 24] = [
+    0, 1, 2, 3, 4, 5, 6, 8, 10, 14, 18, 26, 34, 50, 66, 98, 130, 194, 322, 578, 1090, 2114, 6210,
+    22594,
+];
+const INSERT_LENGTH_EXTRA: [u32; 24] = [
+    0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 7, 8, 9, 10, 12, 14, 24,
+];
+const COPY_LENGTH_BASE: [usize; 24] = [
+    2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 14, 18, 22, 30, 38, 54, 70, 102, 134, 198, 326, 582, 1094, 2118,
+];
+const COPY_LENGTH_EXTRA: [u32; 24] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 7, 8, 9, 10, 24,
+];
+/// 挿入＆コピー長符号の上位 3 ビットごとの、挿入長符号とコピー長符号の開始位置
+const COMMAND_RANGES: [(usize, usize); 11] = [
+    (0, 0),
+    (0, 8),
+    (0, 0),
+    (0, 8),
+    (8, 0),
+    (8, 8),
+    (0, 16),
+    (16, 0),
+    (8, 16),
+    (16, 8),
+    (16, 16),
+];
+
+/// 静的辞書の単語に適用する変換
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transform {
+    Identity,
+    OmitLast(usize),
+    UppercaseFirst,
+    UppercaseAll,
+    OmitFirst(usize),
+}
+
+fn invalid(message: &str) -> Error {
+    Error::UnexpectedInput(format!("invalid brotli data: {}", message))
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_cnt: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_cnt: 0,
+        }
+    }
+
+    fn bits(&mut self, need: u32) -> Result<u32, Error> {
+        let mut value = self.bit_buf;
+        while self.bit_cnt < need {
+            if self.pos >= self.data.len() {
+                return Err(invalid("unexpected end of data"));
+            }
+            value |= (self.data[self.pos] as u32) << self.bit_cnt;
+            self.pos += 1;
+            self.bit_cnt += 8;
+        }
+        self.bit_buf = value >> need;
+        self.bit_cnt -= need;
+        Ok(value & ((1u32 << need) - 1))
+    }
+
+    /// バイト境界まで読み飛ばす。埋め草のビットは 0 でなければならない
+    fn align_to_byte(&mut self) -> Result<(), Error> {
+        if self.bits(self.bit_cnt)? != 0 {
+            return Err(invalid("non-zero padding bits"));
+        }
+        Ok(())
+    }
+}
+
+/// 符号長ごとの個数と、符号順に並べたシンボルで表した正準ハフマン符号
+struct Huffman {
+    count: [u16; MAX_BITS + 1],
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u16]) -> Self {
+        let mut count = [0u16; MAX_BITS + 1];
+        for len in lengths {
+            count[*len as usize] += 1;
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + count[len];
+        }
+
+        let mut symbol = vec![0u16; lengths.len() - count[0] as usize];
+        for (sym, len) in lengths.iter().enumerate() {
+            if *len != 0 {
+                symbol[offsets[*len as usize] as usize] = sym as u16;
+                offsets[*len as usize] += 1;
+            }
+        }
+
+        Self { count, symbol }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<usize, Error> {
+        // シンボルが 1 つしかない符号はビットを読まない
+        if self.symbol.len() == 1 {
+            return Ok(self.symbol[0] as usize);
+        }
+
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.count[len] as i32;
+            if code - count < first {
+                return Ok(self.symbol[(index + (code - first)) as usize] as usize);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(invalid("ran out of huffman codes"))
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-3.4
+fn read_simple_prefix_code(reader: &mut BitReader, alphabet_size: usize) -> Result<Huffman, Error> {
+    let mut alphabet_bits = 0;
+    while (1 << alphabet_bits) < alphabet_size {
+        alphabet_bits += 1;
+    }
+
+    let nsym = reader.bits(2)? as usize + 1;
+    let mut symbols = [0usize; 4];
+    for i in 0..nsym {
+        let symbol = reader.bits(alphabet_bits)? as usize;
+        if symbol >= alphabet_size || symbols[..i].contains(&symbol) {
+            return Err(invalid("invalid simple prefix code"));
+        }
+        symbols[i] = symbol;
+    }
+
+    let code_lengths: &[u16] = match nsym {
+        1 => &[1],
+        2 => &[1, 1],
+        3 => &[1, 2, 2],
+        _ if reader.bits(1)? == 0 => &[2, 2, 2, 2],
+        _ => &[1, 2, 3, 3],
+    };
+    let mut lengths = vec![0u16; alphabet_size];
+    for (symbol, len) in symbols.iter().zip(code_lengths) {
+        lengths[*symbol] = *len;
+    }
+    Ok(Huffman::new(&lengths))
+}
+
+/// 符号長の符号長を表す固定の可変長符号
+fn read_code_length_code_length(reader: &mut BitReader) -> Result<u16, Error> {
+    Ok(match reader.bits(2)? {
+        0 => 0,
+        1 => 4,
+        2 => 3,
+        _ if reader.bits(1)? == 0 => 2,
+        _ if reader.bits(1)? == 0 => 1,
+        _ => 5,
+    })
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-3.5
+fn read_complex_prefix_code(
+    reader: &mut BitReader,
+    alphabet_size: usize,
+    hskip: usize,
+) -> Result<Huffman, Error> {
+    let mut code_length_lengths = [0u16; 18];
+    let mut space: i32 = 32;
+    let mut num_codes = 0;
+    for index in CODE_LENGTH_CODE_ORDER.iter().skip(hskip) {
+        let len = read_code_length_code_length(reader)?;
+        code_length_lengths[*index] = len;
+        if len != 0 {
+            space -= 32 >> len;
+            num_codes += 1;
+            if space <= 0 {
+                break;
+            }
+        }
+    }
+    if num_codes != 1 && space != 0 {
+        return Err(invalid("invalid code length code"));
+    }
+    let code_length_code = Huffman::new(&code_length_lengths);
+
+    let mut lengths = vec![0u16; alphabet_size];
+    let mut symbol = 0;
+    let mut prev_len = 8;
+    let mut repeat = 0;
+    let mut repeat_len = 0;
+    let mut space: i32 = 32768;
+    while symbol < alphabet_size && space > 0 {
+        let code_len = code_length_code.decode(reader)? as u16;
+        if code_len < 16 {
+            repeat = 0;
+            lengths[symbol] = code_len;
+            symbol += 1;
+            if code_len != 0 {
+                prev_len = code_len;
+                space -= 32768 >> code_len;
+            }
+            continue;
+        }
+
+        // 16 は直前の 0 以外の符号長、17 は 0 を繰り返す。続けて現れると回数が掛け合わされる
+        let (extra_bits, new_len) = if code_len == 16 {
+            (2, prev_len)
+        } else {
+            (3, 0)
+        };
+        if repeat_len != new_len {
+            repeat = 0;
+            repeat_len = new_len;
+        }
+        let old_repeat = repeat;
+        if repeat > 0 {
+            repeat = (repeat - 2) << extra_bits;
+        }
+        repeat += reader.bits(extra_bits)? as usize + 3;
+        let delta = repeat - old_repeat;
+        if symbol + delta > alphabet_size {
+            return Err(invalid("too many code lengths"));
+        }
+        for length in lengths.iter_mut().skip(symbol).take(delta) {
+            *length = new_len;
+        }
+        symbol += delta;
+        if new_len != 0 {
+            space -= (delta as i32) * (32768 >> new_len);
+        }
+    }
+    if space != 0 {
+        return Err(invalid("incomplete huffman code"));
+    }
+
+    Ok(Huffman::new(&lengths))
+}
+
+fn read_prefix_code(reader: &mut BitReader, alphabet_size: usize) -> Result<Huffman, Error> {
+    match reader.bits(2)? {
+        1 => read_simple_prefix_code(reader, alphabet_size),
+        hskip => read_complex_prefix_code(reader, alphabet_size, hskip as usize),
+    }
+}
+
+/// NBLTYPES や NTREES に使われる 1 から 256 までの可変長の値
+fn read_count(reader: &mut BitReader) -> Result<usize, Error> {
+    if reader.bits(1)? == 0 {
+        return Ok(1);
+    }
+    let n = reader.bits(3)?;
+    Ok((1 << n) + reader.bits(n)? as usize + 1)
+}
+
+fn read_block_count(reader: &mut BitReader, code: &Huffman) -> Result<usize, Error> {
+    let symbol = code.decode(reader)?;
+    Ok(BLOCK_COUNT_BASE[symbol] + reader.bits(BLOCK_COUNT_EXTRA[symbol])? as usize)
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-7.3
+fn read_context_map(
+    reader: &mut BitReader,
+    size: usize,
+    num_trees: usize,
+) -> Result<Vec<usize>, Error> {
+    let mut map = vec![0usize; size];
+    if num_trees < 2 {
+        return Ok(map);
+    }
+
+    let rle_max = if reader.bits(1)? == 1 {
+        reader.bits(4)? as usize + 1
+    } else {
+        0
+    };
+    let code = read_prefix_code(reader, num_trees + rle_max)?;
+    let mut i = 0;
+    while i < size {
+        let symbol = code.decode(reader)?;
+        if symbol == 0 {
+            i += 1;
+        } else if symbol <= rle_max {
+            let repeat = (1 << symbol) + reader.bits(symbol as u32)? as usize;
+            if i + repeat > size {
+                return Err(invalid("context map overflow"));
+            }
+            // map は 0 で初期化済み
+            i += repeat;
+        } else {
+            map[i] = symbol - rle_max;
+            i += 1;
+        }
+    }
+
+    // 逆 move-to-front 変換
+    if reader.bits(1)? == 1 {
+        let mut mtf: Vec<usize> = (0..256).collect();
+        for value in map.iter_mut() {
+            let index = *value;
+            *value = mtf[index];
+            let v = mtf.remove(index);
+            mtf.insert(0, v);
+        }
+    }
+
+    Ok(map)
+}
+
+/// 種類 (リテラル、挿入＆コピー長、距離) ごとのブロックの切り替え
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-6
+struct BlockSwitch {
+    num_types: usize,
+    type_code: Option<Huffman>,
+    count_code: Option<Huffman>,
+    current: usize,
+    previous: usize,
+    remaining: usize,
+}
+
+impl BlockSwitch {
+    fn read(reader: &mut BitReader) -> Result<Self, Error> {
+        let num_types = read_count(reader)?;
+        if num_types < 2 {
+            return Ok(Self {
+                num_types,
+                type_code: None,
+                count_code: None,
+                current: 0,
+                previous: 1,
+                remaining: usize::MAX,
+            });
+        }
+
+        let type_code = read_prefix_code(reader, num_types + 2)?;
+        let count_code = read_prefix_code(reader, NUM_BLOCK_COUNT_CODES)?;
+        let remaining = read_block_count(reader, &count_code)?;
+        Ok(Self {
+            num_types,
+            type_code: Some(type_code),
+            count_code: Some(count_code),
+            current: 0,
+            previous: 1,
+            remaining,
+        })
+    }
+
+    /// 次のシンボルが属するブロックの種類を返す
+    fn next(&mut self, reader: &mut BitReader) -> Result<usize, Error> {
+        if self.remaining == 0 {
+            let (Some(type_code), Some(count_code)) = (&self.type_code, &self.count_code) else {
+                return Err(invalid("block count exceeded"));
+            };
+            let mut block_type = match type_code.decode(reader)? {
+                0 => self.previous,
+                1 => self.current + 1,
+                n => n - 2,
+            };
+            if block_type >= self.num_types {
+                block_type -= self.num_types;
+            }
+            self.previous = self.current;
+            self.current = block_type;
+            self.remaining = read_block_count(reader, count_code)?;
+        }
+        self.remaining -= 1;
+        Ok(self.current)
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-7.1
+fn literal_context(mode: u32, p1: u8, p2: u8) -> usize {
+    let (p1, p2) = (p1 as usize, p2 as usize);
+    match mode {
+        0 => p1 & 0x3f,
+        1 => p1 >> 2,
+        2 => (UTF8_LUT0[p1] | UTF8_LUT1[p2]) as usize,
+        _ => ((SIGNED_LUT[p1] << 3) | SIGNED_LUT[p2]) as usize,
+    }
+}
+
+fn to_upper_case(word: &mut [u8]) -> usize {
+    if word[0] < 0xc0 {
+        word[0] = word[0].to_ascii_uppercase();
+        return 1;
+    }
+    if word[0] < 0xe0 {
+        if let Some(b) = word.get_mut(1) {
+            *b ^= 32;
+        }
+        return 2;
+    }
+    if let Some(b) = word.get_mut(2) {
+        *b ^= 5;
+    }
+    3
+}
+
+/// 静的辞書の単語を変換して out に追加する
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-8
+fn dictionary_word(out: &mut Vec<u8>, len: usize, word_id: usize) -> Result<(), Error> {
+    if !(4..=24).contains(&len) {
+        return Err(invalid("invalid dictionary word length"));
+    }
+    let bits = DICTIONARY_SIZE_BITS[len];
+    let transform_id = word_id >> bits;
+    if transform_id >= TRANSFORMS.len() {
+        return Err(invalid("invalid dictionary transform"));
+    }
+    let offset = DICTIONARY_OFFSETS[len] + (word_id & ((1 << bits) - 1)) * len;
+    let (prefix, transform, suffix) = TRANSFORMS[transform_id];
+
+    let mut word = &DICTIONARY[offset..offset + len];
+    match transform {
+        Transform::OmitLast(n) => word = &word[..len.saturating_sub(n)],
+        Transform::OmitFirst(n) => word = &word[n.min(len)..],
+        _ => {}
+    }
+
+    let mut word = word.to_vec();
+    match transform {
+        Transform::UppercaseFirst if !word.is_empty() => {
+            to_upper_case(&mut word);
+        }
+        Transform::UppercaseAll => {
+            let mut i = 0;
+            while i < word.len() {
+                i += to_upper_case(&mut word[i..]);
+            }
+        }
+        _ => {}
+    }
+
+    reserve(out, prefix.len() + word.len() + suffix.len())?;
+    out.extend_from_slice(prefix);
+    out.extend_from_slice(&word);
+    out.extend_from_slice(suffix);
+    Ok(())
+}
+
+/// 圧縮されたメタブロックを 1 つ展開する
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-9.2
+fn compressed_meta_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    meta_block_len: usize,
+    window_size: usize,
+    distances: &mut [usize; 4],
+) -> Result<(), Error> {
+    let mut literal_blocks = BlockSwitch::read(reader)?;
+    let mut command_blocks = BlockSwitch::read(reader)?;
+    let mut distance_blocks = BlockSwitch::read(reader)?;
+
+    let postfix_bits = reader.bits(2)?;
+    let num_direct = (reader.bits(4)? as usize) << postfix_bits;
+    let postfix_mask = (1 << postfix_bits) - 1;
+
+    let mut context_modes = vec![0u32; literal_blocks.num_types];
+    for mode in context_modes.iter_mut() {
+        *mode = reader.bits(2)?;
+    }
+
+    let num_literal_trees = read_count(reader)?;
+    let literal_context_map =
+        read_context_map(reader, literal_blocks.num_types * 64, num_literal_trees)?;
+    let num_distance_trees = read_count(reader)?;
+    let distance_context_map =
+        read_context_map(reader, distance_blocks.num_types * 4, num_distance_trees)?;
+
+    let mut literal_codes = Vec::new();
+    for _ in 0..num_literal_trees {
+        literal_codes.push(read_prefix_code(reader, NUM_LITERAL_CODES)?);
+    }
+    let mut command_codes = Vec::new();
+    for _ in 0..command_blocks.num_types {
+        command_codes.push(read_prefix_code(reader, NUM_COMMAND_CODES)?);
+    }
+    let num_distance_codes = 16 + num_direct + (48 << postfix_bits);
+    let mut distance_codes = Vec::new();
+    for _ in 0..num_distance_trees {
+        distance_codes.push(read_prefix_code(reader, num_distance_codes)?);
+    }
+
+    let end = out.len() + meta_block_len;
+    while out.len() < end {
+        let command_type = command_blocks.next(reader)?;
+        let command = command_codes[command_type].decode(reader)?;
+        let (insert_offset, copy_offset) = COMMAND_RANGES[command >> 6];
+        let insert_code = insert_offset + ((command >> 3) & 7);
+        let copy_code = copy_offset + (command & 7);
+        let insert_len = INSERT_LENGTH_BASE[insert_code]
+            + reader.bits(INSERT_LENGTH_EXTRA[insert_code])? as usize;
+        let copy_len =
+            COPY_LENGTH_BASE[copy_code] + reader.bits(COPY_LENGTH_EXTRA[copy_code])? as usize;
+
+        if out.len() + insert_len > end {
+            return Err(invalid("insert length exceeds meta-block"));
+        }
+        reserve(out, insert_len)?;
+        for _ in 0..insert_len {
+            let literal_type = literal_blocks.next(reader)?;
+            let p1 = out.last().copied().unwrap_or(0);
+            let p2 = out.len().checked_sub(2).map(|i| out[i]).unwrap_or(0);
+            let context = literal_context(context_modes[literal_type], p1, p2);
+            let tree = literal_context_map[literal_type * 64 + context];
+            out.push(literal_codes[tree].decode(reader)? as u8);
+        }
+        // メタブロックの最後のコマンドのコピー長は使われない
+        if out.len() == end {
+            break;
+        }
+
+        // 挿入＆コピー長符号が 128 未満のときは、距離符号 0 (直前の距離) が暗黙に使われる
+        let distance_code = if command < 128 {
+            0
+        } else {
+            let distance_type = distance_blocks.next(reader)?;
+            let context = if copy_len > 4 { 3 } else { copy_len - 2 };
+            let tree = distance_context_map[distance_type * 4 + context];
+            distance_codes[tree].decode(reader)?
+        };
+
+        let distance = match distance_code {
+            0..=15 => {
+                let (base, delta): (usize, isize) = match distance_code {
+                    0..=3 => (distance_code, 0),
+                    4..=9 => (0, [-1, 1, -2, 2, -3, 3][distance_code - 4]),
+                    _ => (1, [-1, 1, -2, 2, -3, 3][distance_code - 10]),
+                };
+                let distance = distances[base] as isize + delta;
+                if distance <= 0 {
+                    return Err(invalid("invalid distance"));
+                }
+                distance as usize
+            }
+            _ if distance_code < 16 + num_direct => distance_code - 15,
+            _ => {
+                let code = distance_code - num_direct - 16;
+                let extra_bits = 1 + (code >> (postfix_bits + 1)) as u32;
+                let high = code >> postfix_bits;
+                let low = code & postfix_mask;
+                let offset = ((2 + (high & 1)) << extra_bits) - 4;
+                ((offset + reader.bits(extra_bits)? as usize) << postfix_bits)
+                    + low
+                    + num_direct
+                    + 1
+            }
+        };
+
+        // 出力済みのバイト数 (とウィンドウ) より遠い距離は静的辞書を指す
+        let max_distance = out.len().min(window_size);
+        if distance > max_distance {
+            dictionary_word(out, copy_len, distance - max_distance - 1)?;
+            if out.len() > end {
+                return Err(invalid("copy length exceeds meta-block"));
+            }
+            continue;
+        }
+
+        if distance_code != 0 {
+            distances.copy_within(0..3, 1);
+            distances[0] = distance;
+        }
+        if out.len() + copy_len > end {
+            return Err(invalid("copy length exceeds meta-block"));
+        }
+        reserve(out, copy_len)?;
+        // 距離より長い一致は自分自身をコピーし続ける必要があるので 1 バイトずつ写す
+        let start = out.len() - distance;
+        for i in 0..copy_len {
+            let b = out[start + i];
+            out.push(b);
+        }
+    }
+
+    Ok(())
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc7932#section-9.1
+fn read_window_bits(reader: &mut BitReader) -> Result<u32, Error> {
+    if reader.bits(1)? == 0 {
+        return Ok(16);
+    }
+    let n = reader.bits(3)?;
+    if n != 0 {
+        return Ok(17 + n);
+    }
+    match reader.bits(3)? {
+        0 => Ok(17),
+        1 => Err(invalid("large window is not supported")),
+        n => Ok(8 + n),
+    }
+}
+
+/// brotli で圧縮されたデータを展開する
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut reader = BitReader::new(data);
+    let mut out = Vec::new();
+    let window_size = (1usize << read_window_bits(&mut reader)?) - 16;
+    // 直近 4 つの距離。先頭が最新
+    let mut distances = [4, 11, 15, 16];
+
+    loop {
+        let is_last = reader.bits(1)? == 1;
+        if is_last && reader.bits(1)? == 1 {
+            break;
+        }
+
+        let nibbles = match reader.bits(2)? {
+            3 => 0,
+            n => n + 4,
+        };
+        if nibbles == 0 {
+            // メタデータは読み飛ばす
+            if is_last || reader.bits(1)? != 0 {
+                return Err(invalid("invalid metadata block"));
+            }
+            let skip_bytes = reader.bits(2)?;
+            let mut skip_len = 0;
+            for i in 0..skip_bytes {
+                skip_len |= (reader.bits(8)? as usize) << (i * 8);
+            }
+            if skip_bytes > 0 {
+                skip_len += 1;
+            }
+            reader.align_to_byte()?;
+            if reader.pos + skip_len > data.len() {
+                return Err(invalid("unexpected end of data"));
+            }
+            reader.pos += skip_len;
+            continue;
+        }
+
+        let mut meta_block_len = 0;
+        for i in 0..nibbles {
+            let nibble = reader.bits(4)? as usize;
+            if i + 1 == nibbles && nibbles > 4 && nibble == 0 {
+                return Err(invalid("invalid meta-block length"));
+            }
+            meta_block_len |= nibble << (i * 4);
+        }
+        meta_block_len += 1;
+
+        if !is_last && reader.bits(1)? == 1 {
+            reader.align_to_byte()?;
+            let start = reader.pos;
+            if start + meta_block_len > data.len() {
+                return Err(invalid("unexpected end of data"));
+            }
+            reserve(&mut out, meta_block_len)?;
+            out.extend_from_slice(&data[start..start + meta_block_len]);
+            reader.pos = start + meta_block_len;
+            continue;
+        }
+
+        compressed_meta_block(
+            &mut reader,
+            &mut out,
+            meta_block_len,
+            window_size,
+            &mut distances,
+        )?;
+        if is_last {
+            break;
+        }
+    }
+
+    reader.align_to_byte()?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(decompress(&[0x3b]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_uncompressed_meta_block() {
+        let data = [
+            0x8b, 0x07, 0x80, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa,
+            0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x03,
+        ];
+        let expected: Vec<u8> = (0..=255).step_by(17).collect();
+        assert_eq!(decompress(&data), Ok(expected));
+    }
+
+    #[test]
+    fn test_backward_reference() {
+        let data = [
+            0x1b, 0x10, 0x00, 0xf8, 0x8d, 0x94, 0x6e, 0xde, 0x44, 0x55, 0x86, 0x96, 0x6c, 0x20,
+            0x6f, 0x01, 0x4f, 0x1c, 0x60, 0x1c,
+        ];
+        assert_eq!(decompress(&data), Ok(b"hello hello hello".to_vec()));
+    }
+
+    #[test]
+    fn test_static_dictionary() {
+        let data = [
+            0x1b, 0x5a, 0x00, 0x58, 0x1c, 0xa9, 0x53, 0x9f, 0x3b, 0x5e, 0xa2, 0x51, 0xf1, 0x24,
+            0x48, 0x19, 0x5d, 0xfa, 0xb0, 0xb5, 0x81, 0x43, 0x0e, 0x5c, 0xda, 0xda, 0x6e, 0x9e,
+            0xb7, 0x88, 0x82, 0x0d, 0x38, 0x95, 0xed, 0xc1, 0x13, 0x98, 0x75, 0x3a, 0xf2, 0x29,
+            0x91, 0xf8, 0x65, 0x05, 0xcc, 0xea, 0x0b, 0xda, 0x02, 0x21, 0x0f, 0x0a,
+        ];
+        let expected = b"<html><head><title>The information of the world</title></head>\
+            <body>Home page</body></html>";
+        assert_eq!(decompress(&data), Ok(expected.to_vec()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(decompress(&[]).is_err());
+        assert!(decompress(&[0x1b, 0x10, 0x00, 0xf8, 0x8d, 0x94]).is_err());
+        // 最後の空のメタブロックの後の埋め草が 0 でない
+        assert!(decompress(&[0xbb]).is_err());
+    }
+}
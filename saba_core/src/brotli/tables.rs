@@ -0,0 +1,166 @@
+//! https://datatracker.ietf.org/doc/html/rfc7932#section-7.1
+//! https://datatracker.ietf.org/doc/html/rfc7932#appendix-B
+
+use super::Transform;
+
+/// UTF8 コンテキストで直前のバイトから引く値
+pub(super) static UTF8_LUT0: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 4, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    8, 12, 16, 12, 12, 20, 12, 16, 24, 28, 12, 12, 32, 12, 36, 12, 44, 44, 44, 44, 44, 44, 44, 44,
+    44, 44, 32, 32, 24, 40, 28, 12, 12, 48, 52, 52, 52, 48, 52, 52, 52, 48, 52, 52, 52, 52, 52, 48,
+    52, 52, 52, 52, 52, 48, 52, 52, 52, 52, 52, 24, 12, 28, 12, 12, 12, 56, 60, 60, 60, 56, 60, 60,
+    60, 56, 60, 60, 60, 60, 60, 56, 60, 60, 60, 60, 60, 56, 60, 60, 60, 60, 60, 24, 12, 28, 12, 0,
+    0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1,
+    0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1,
+    2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3,
+    2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3, 2, 3,
+];
+
+/// UTF8 コンテキストで 2 つ前のバイトから引く値
+pub(super) static UTF8_LUT1: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1,
+    1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1,
+    1, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 1, 1, 1, 1, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+];
+
+/// Signed コンテキストでバイトを 0 から 7 に分類する値
+pub(super) static SIGNED_LUT: [u8; 256] = [
+    0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+    3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+    4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
+    4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
+    5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5,
+    5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 7,
+];
+
+/// 静的辞書の単語に適用する (前置文字列, 変換, 後置文字列)
+pub(super) static TRANSFORMS: [(&[u8], Transform, &[u8]); 121] = [
+    (b"", Transform::Identity, b""),
+    (b"", Transform::Identity, b" "),
+    (b" ", Transform::Identity, b" "),
+    (b"", Transform::OmitFirst(1), b""),
+    (b"", Transform::UppercaseFirst, b" "),
+    (b"", Transform::Identity, b" the "),
+    (b" ", Transform::Identity, b""),
+    (b"s ", Transform::Identity, b" "),
+    (b"", Transform::Identity, b" of "),
+    (b"", Transform::UppercaseFirst, b""),
+    (b"", Transform::Identity, b" and "),
+    (b"", Transform::OmitFirst(2), b""),
+    (b"", Transform::OmitLast(1), b""),
+    (b", ", Transform::Identity, b" "),
+    (b"", Transform::Identity, b", "),
+    (b" ", Transform::UppercaseFirst, b" "),
+    (b"", Transform::Identity, b" in "),
+    (b"", Transform::Identity, b" to "),
+    (b"e ", Transform::Identity, b" "),
+    (b"", Transform::Identity, b"\""),
+    (b"", Transform::Identity, b"."),
+    (b"", Transform::Identity, b"\">"),
+    (b"", Transform::Identity, b"\x0a"),
+    (b"", Transform::OmitLast(3), b""),
+    (b"", Transform::Identity, b"]"),
+    (b"", Transform::Identity, b" for "),
+    (b"", Transform::OmitFirst(3), b""),
+    (b"", Transform::OmitLast(2), b""),
+    (b"", Transform::Identity, b" a "),
+    (b"", Transform::Identity, b" that "),
+    (b" ", Transform::UppercaseFirst, b""),
+    (b"", Transform::Identity, b". "),
+    (b".", Transform::Identity, b""),
+    (b" ", Transform::Identity, b", "),
+    (b"", Transform::OmitFirst(4), b""),
+    (b"", Transform::Identity, b" with "),
+    (b"", Transform::Identity, b"'"),
+    (b"", Transform::Identity, b" from "),
+    (b"", Transform::Identity, b" by "),
+    (b"", Transform::OmitFirst(5), b""),
+    (b"", Transform::OmitFirst(6), b""),
+    (b" the ", Transform::Identity, b""),
+    (b"", Transform::OmitLast(4), b""),
+    (b"", Transform::Identity, b". The "),
+    (b"", Transform::UppercaseAll, b""),
+    (b"", Transform::Identity, b" on "),
+    (b"", Transform::Identity, b" as "),
+    (b"", Transform::Identity, b" is "),
+    (b"", Transform::OmitLast(7), b""),
+    (b"", Transform::OmitLast(1), b"ing "),
+    (b"", Transform::Identity, b"\x0a\x09"),
+    (b"", Transform::Identity, b":"),
+    (b" ", Transform::Identity, b". "),
+    (b"", Transform::Identity, b"ed "),
+    (b"", Transform::OmitFirst(9), b""),
+    (b"", Transform::OmitFirst(7), b""),
+    (b"", Transform::OmitLast(6), b""),
+    (b"", Transform::Identity, b"("),
+    (b"", Transform::UppercaseFirst, b", "),
+    (b"", Transform::OmitLast(8), b""),
+    (b"", Transform::Identity, b" at "),
+    (b"", Transform::Identity, b"ly "),
+    (b" the ", Transform::Identity, b" of "),
+    (b"", Transform::OmitLast(5), b""),
+    (b"", Transform::OmitLast(9), b""),
+    (b" ", Transform::UppercaseFirst, b", "),
+    (b"", Transform::UppercaseFirst, b"\""),
+    (b".", Transform::Identity, b"("),
+    (b"", Transform::UppercaseAll, b" "),
+    (b"", Transform::UppercaseFirst, b"\">"),
+    (b"", Transform::Identity, b"=\""),
+    (b" ", Transform::Identity, b"."),
+    (b".com/", Transform::Identity, b""),
+    (b" the ", Transform::Identity, b" of the "),
+    (b"", Transform::UppercaseFirst, b"'"),
+    (b"", Transform::Identity, b". This "),
+    (b"", Transform::Identity, b","),
+    (b".", Transform::Identity, b" "),
+    (b"", Transform::UppercaseFirst, b"("),
+    (b"", Transform::UppercaseFirst, b"."),
+    (b"", Transform::Identity, b" not "),
+    (b" ", Transform::Identity, b"=\""),
+    (b"", Transform::Identity, b"er "),
+    (b" ", Transform::UppercaseAll, b" "),
+    (b"", Transform::Identity, b"al "),
+    (b" ", Transform::UppercaseAll, b""),
+    (b"", Transform::Identity, b"='"),
+    (b"", Transform::UppercaseAll, b"\""),
+    (b"", Transform::UppercaseFirst, b". "),
+    (b" ", Transform::Identity, b"("),
+    (b"", Transform::Identity, b"ful "),
+    (b" ", Transform::UppercaseFirst, b". "),
+    (b"", Transform::Identity, b"ive "),
+    (b"", Transform::Identity, b"less "),
+    (b"", Transform::UppercaseAll, b"'"),
+    (b"", Transform::Identity, b"est "),
+    (b" ", Transform::UppercaseFirst, b"."),
+    (b"", Transform::UppercaseAll, b"\">"),
+    (b" ", Transform::Identity, b"='"),
+    (b"", Transform::UppercaseFirst, b","),
+    (b"", Transform::Identity, b"ize "),
+    (b"", Transform::UppercaseAll, b"."),
+    (b"\xc2\xa0", Transform::Identity, b""),
+    (b" ", Transform::Identity, b","),
+    (b"", Transform::UppercaseFirst, b"=\""),
+    (b"", Transform::UppercaseAll, b"=\""),
+    (b"", Transform::Identity, b"ous "),
+    (b"", Transform::UppercaseAll, b", "),
+    (b"", Transform::UppercaseFirst, b"='"),
+    (b" ", Transform::UppercaseFirst, b","),
+    (b" ", Transform::UppercaseAll, b"=\""),
+    (b" ", Transform::UppercaseAll, b", "),
+    (b"", Transform::UppercaseAll, b","),
+    (b"", Transform::UppercaseAll, b"("),
+    (b"", Transform::UppercaseAll, b". "),
+    (b" ", Transform::UppercaseAll, b"."),
+    (b"", Transform::UppercaseAll, b"='"),
+    (b" ", Transform::UppercaseAll, b". "),
+    (b" ", Transform::UppercaseFirst, b"=\""),
+    (b" ", Transform::UppercaseAll, b"='"),
+    (b" ", Transform::UppercaseFirst, b"='"),
+];
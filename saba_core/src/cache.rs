@@ -0,0 +1,332 @@
+//! https://datatracker.ietf.org/doc/html/rfc9111
+
+use crate::cookie::parse_cookie_date;
+use crate::http::Headers;
+use crate::http::HttpResponse;
+use crate::url::Url;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    response: HttpResponse,
+    /// レスポンスを保存 (または再検証) した時刻。Unix 時間 (秒)
+    stored_at: i64,
+    freshness_lifetime: i64,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self, now: i64) -> bool {
+        now - self.stored_at < self.freshness_lifetime
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HttpCache {
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl HttpCache {
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// 再検証せずにそのまま使えるレスポンスがあれば返す
+    pub fn fresh_response(&self, url: &Url, now: i64) -> Option<HttpResponse> {
+        match self.entries.get(&cache_key(url)) {
+            Some(entry) if entry.is_fresh(now) => Some(entry.response.clone()),
+            _ => None,
+        }
+    }
+
+    /// 古くなったエントリを再検証するための条件付きリクエストのヘッダを返す
+    pub fn validators(&self, url: &Url) -> Headers {
+        let mut headers = Headers::new();
+        let entry = match self.entries.get(&cache_key(url)) {
+            Some(entry) => entry,
+            None => return headers,
+        };
+
+        let stored = entry.response.headers();
+        if let Some(etag) = stored.get("ETag") {
+            headers.append("If-None-Match", &etag);
+        }
+        if let Some(last_modified) = stored.get("Last-Modified") {
+            headers.append("If-Modified-Since", &last_modified);
+        }
+        headers
+    }
+
+    /// GET のレスポンスを保存する。保存できないレスポンスの場合は何もしない
+    pub fn store(&mut self, url: &Url, response: &HttpResponse, now: i64) {
+        let key = cache_key(url);
+        if response.status_code() != 200 {
+            return;
+        }
+
+        // Vary: * のレスポンスはどのリクエストにも使えない
+        let vary_any = response
+            .headers()
+            .get_all("Vary")
+            .iter()
+            .any(|v| v.split(',').any(|field| field.trim() == "*"));
+        let directives = cache_control(response);
+        if vary_any || directives.iter().any(|(name, _)| name == "no-store") {
+            self.entries.remove(&key);
+            return;
+        }
+
+        let freshness_lifetime = freshness_lifetime(response, &directives);
+        let has_validator =
+            response.headers().contains("ETag") || response.headers().contains("Last-Modified");
+        if freshness_lifetime <= 0 && !has_validator {
+            self.entries.remove(&key);
+            return;
+        }
+
+        self.entries.insert(
+            key,
+            CacheEntry {
+                response: response.clone(),
+                stored_at: now,
+                freshness_lifetime,
+            },
+        );
+    }
+
+    /// 304 Not Modified を受け取ったときに、保存していたレスポンスを新しいものとして返す
+    pub fn revalidate(
+        &mut self,
+        url: &Url,
+        not_modified: &HttpResponse,
+        now: i64,
+    ) -> Option<HttpResponse> {
+        let entry = self.entries.get_mut(&cache_key(url))?;
+        entry.stored_at = now;
+
+        // https://datatracker.ietf.org/doc/html/rfc9111#section-3.2
+        //
+        // 304 に含まれるヘッダで保存していたヘッダを置き換える。ボディの長さは変わらない
+        let updated = not_modified.headers();
+        let stored = entry.response.headers_mut();
+        for header in &updated {
+            if !header.name().eq_ignore_ascii_case("Content-Length") {
+                stored.remove(&header.name());
+            }
+        }
+        for header in &updated {
+            if !header.name().eq_ignore_ascii_case("Content-Length") {
+                stored.append(&header.name(), &header.value());
+            }
+        }
+
+        let directives = cache_control(&entry.response);
+        entry.freshness_lifetime = freshness_lifetime(&entry.response, &directives);
+
+        Some(entry.response.clone())
+    }
+
+    pub fn remove(&mut self, url: &Url) {
+        self.entries.remove(&cache_key(url));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// フラグメントを除いた URL をキーにする
+fn cache_key(url: &Url) -> String {
+    format!(
+        "{}://{}:{}/{}?{}",
+        url.scheme(),
+        url.host(),
+        url.port(),
+        url.raw_path(),
+        url.raw_searchpart()
+    )
+}
+
+/// Cache-Control ヘッダを (ディレクティブ名, 値) のリストにする
+fn cache_control(response: &HttpResponse) -> Vec<(String, String)> {
+    let mut directives = Vec::new();
+    for value in response.headers().get_all("Cache-Control") {
+        for directive in value.split(',') {
+            let (name, value) = match directive.split_once('=') {
+                Some((n, v)) => (n, v.trim().trim_matches('"')),
+                None => (directive, ""),
+            };
+            directives.push((name.trim().to_ascii_lowercase(), value.to_string()));
+        }
+    }
+    directives
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc9111#section-4.2.1
+fn freshness_lifetime(response: &HttpResponse, directives: &[(String, String)]) -> i64 {
+    if directives.iter().any(|(name, _)| name == "no-cache") {
+        return 0;
+    }
+
+    if let Some((_, value)) = directives.iter().find(|(name, _)| name == "max-age") {
+        return value.parse().unwrap_or(0);
+    }
+
+    // 不正な Expires は過去の時刻として扱う
+    let headers = response.headers();
+    let date = headers.get("Date").and_then(|d| parse_cookie_date(&d));
+    let expires = headers.get("Expires").and_then(|e| parse_cookie_date(&e));
+    match (date, expires) {
+        (Some(date), Some(expires)) => expires - date,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::new(s.to_string())
+            .parse()
+            .expect("failed to parse url")
+    }
+
+    fn response(raw: &str) -> HttpResponse {
+        HttpResponse::new(raw.to_string()).expect("failed to parse http response")
+    }
+
+    #[test]
+    fn test_max_age() {
+        let mut cache = HttpCache::new();
+        let u = url("http://example.com/index.html");
+        cache.store(
+            &u,
+            &response("HTTP/1.1 200 OK\nCache-Control: public, max-age=60\n\nbody"),
+            1000,
+        );
+
+        assert_eq!(
+            cache.fresh_response(&u, 1059).map(|r| r.body()),
+            Some("body".to_string())
+        );
+        assert!(cache.fresh_response(&u, 1060).is_none());
+        assert!(cache
+            .fresh_response(&url("http://example.com/index.html?x=1"), 1000)
+            .is_none());
+    }
+
+    #[test]
+    fn test_fragment_is_ignored() {
+        let mut cache = HttpCache::new();
+        cache.store(
+            &url("http://example.com/a#one"),
+            &response("HTTP/1.1 200 OK\nCache-Control: max-age=60\n\nbody"),
+            0,
+        );
+
+        assert!(cache
+            .fresh_response(&url("http://example.com/a#two"), 0)
+            .is_some());
+    }
+
+    #[test]
+    fn test_expires() {
+        let mut cache = HttpCache::new();
+        let u = url("http://example.com/");
+        cache.store(
+            &u,
+            &response("HTTP/1.1 200 OK\nDate: Wed, 21 Oct 2015 07:28:00 GMT\nExpires: Wed, 21 Oct 2015 07:38:00 GMT\n\nbody"),
+            0,
+        );
+
+        assert!(cache.fresh_response(&u, 599).is_some());
+        assert!(cache.fresh_response(&u, 600).is_none());
+    }
+
+    #[test]
+    fn test_not_stored() {
+        let mut cache = HttpCache::new();
+        let u = url("http://example.com/");
+        cache.store(
+            &u,
+            &response("HTTP/1.1 200 OK\nCache-Control: no-store, max-age=60\n\nbody"),
+            0,
+        );
+        cache.store(
+            &url("http://example.com/404"),
+            &response("HTTP/1.1 404 NotFound\nCache-Control: max-age=60\n\n"),
+            0,
+        );
+        cache.store(
+            &url("http://example.com/none"),
+            &response("HTTP/1.1 200 OK\n\nbody"),
+            0,
+        );
+
+        cache.store(
+            &url("http://example.com/vary"),
+            &response("HTTP/1.1 200 OK\nVary: Accept, *\nCache-Control: max-age=60\n\nbody"),
+            0,
+        );
+
+        assert!(cache.fresh_response(&u, 0).is_none());
+        assert!(cache
+            .fresh_response(&url("http://example.com/vary"), 0)
+            .is_none());
+        assert!(cache.validators(&url("http://example.com/404")).is_empty());
+        assert!(cache.validators(&url("http://example.com/none")).is_empty());
+    }
+
+    #[test]
+    fn test_revalidate() {
+        let mut cache = HttpCache::new();
+        let u = url("http://example.com/");
+        cache.store(
+            &u,
+            &response("HTTP/1.1 200 OK\nCache-Control: no-cache\nETag: \"v1\"\nLast-Modified: Wed, 21 Oct 2015 07:28:00 GMT\n\nbody"),
+            0,
+        );
+
+        assert!(cache.fresh_response(&u, 0).is_none());
+        let validators = cache.validators(&u);
+        assert_eq!(validators.get("if-none-match"), Some("\"v1\"".to_string()));
+        assert_eq!(
+            validators.get("If-Modified-Since"),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string())
+        );
+
+        let revalidated = cache.revalidate(
+            &u,
+            &response("HTTP/1.1 304 NotModified\nCache-Control: max-age=30\n\n"),
+            100,
+        );
+        assert_eq!(revalidated.map(|r| r.body()), Some("body".to_string()));
+        assert!(cache.fresh_response(&u, 129).is_some());
+        assert!(cache.fresh_response(&u, 130).is_none());
+
+        // 304 のヘッダが保存していたヘッダを置き換える
+        cache.revalidate(
+            &u,
+            &response("HTTP/1.1 304 NotModified\nETag: \"v2\"\nCache-Control: no-cache\n\n"),
+            200,
+        );
+        assert_eq!(
+            cache.validators(&u).get("If-None-Match"),
+            Some("\"v2\"".to_string())
+        );
+        assert!(cache.fresh_response(&u, 200).is_none());
+
+        assert!(cache
+            .revalidate(
+                &url("http://example.com/other"),
+                &response("HTTP/1.1 304 NotModified\n\n"),
+                0
+            )
+            .is_none());
+    }
+}
@@ -0,0 +1,527 @@
+//! https://datatracker.ietf.org/doc/html/rfc6265
+
+use crate::url::Url;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    /// Unix 時間 (秒)。None のときはセッションクッキー
+    expires: Option<i64>,
+    secure: bool,
+    http_only: bool,
+}
+
+impl Cookie {
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn value(&self) -> String {
+        self.value.clone()
+    }
+
+    pub fn domain(&self) -> String {
+        self.domain.clone()
+    }
+
+    pub fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn expires(&self) -> Option<i64> {
+        self.expires
+    }
+
+    pub fn secure(&self) -> bool {
+        self.secure
+    }
+
+    pub fn http_only(&self) -> bool {
+        self.http_only
+    }
+
+    fn is_expired(&self, now: i64) -> bool {
+        match self.expires {
+            Some(expires) => expires <= now,
+            None => false,
+        }
+    }
+
+    fn matches(&self, url: &Url, now: i64) -> bool {
+        if self.is_expired(now) {
+            return false;
+        }
+        if self.secure && url.scheme() != "https" {
+            return false;
+        }
+
+        let host = url.host().to_ascii_lowercase();
+        let domain_matched = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(&host, &self.domain)
+        };
+
+        domain_matched && path_match(&request_path(url), &self.path)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self {
+            cookies: Vec::new(),
+        }
+    }
+
+    pub fn cookies(&self) -> Vec<Cookie> {
+        self.cookies.clone()
+    }
+
+    /// `url` から受け取った Set-Cookie ヘッダの値を保存する。`now` は Unix 時間 (秒)
+    pub fn store(&mut self, url: &Url, set_cookie: &str, now: i64) {
+        let cookie = match parse_set_cookie(url, set_cookie, now) {
+            Some(cookie) => cookie,
+            None => return,
+        };
+
+        self.cookies.retain(|c| {
+            !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+        });
+        // Max-Age=0 や過去の Expires は既存のクッキーの削除として扱う
+        if !cookie.is_expired(now) {
+            self.cookies.push(cookie);
+        }
+    }
+
+    /// `url` へのリクエストに付ける Cookie ヘッダの値を返す
+    pub fn cookie_header(&self, url: &Url, now: i64) -> Option<String> {
+        let mut cookies: Vec<&Cookie> = self
+            .cookies
+            .iter()
+            .filter(|c| c.matches(url, now))
+            .collect();
+        if cookies.is_empty() {
+            return None;
+        }
+        // パスが長いものを先に並べる
+        cookies.sort_by_key(|c| Reverse(c.path.len()));
+
+        let pairs: Vec<String> = cookies
+            .iter()
+            .map(|c| {
+                let mut pair = c.name.clone();
+                pair.push('=');
+                pair.push_str(&c.value);
+                pair
+            })
+            .collect();
+        Some(pairs.join("; "))
+    }
+
+    pub fn remove_expired(&mut self, now: i64) {
+        self.cookies.retain(|c| !c.is_expired(now));
+    }
+
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc6265#section-5.2
+fn parse_set_cookie(url: &Url, set_cookie: &str, now: i64) -> Option<Cookie> {
+    let mut attributes = set_cookie.split(';');
+    let (name, value) = attributes.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let host = url.host().to_ascii_lowercase();
+    let mut cookie = Cookie {
+        name: name.to_string(),
+        value: value.trim().to_string(),
+        domain: host.clone(),
+        host_only: true,
+        path: default_path(url),
+        expires: None,
+        secure: false,
+        http_only: false,
+    };
+    let mut max_age = None;
+    let mut expires = None;
+
+    for attribute in attributes {
+        let (key, value) = match attribute.split_once('=') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => (attribute.trim(), ""),
+        };
+
+        if key.eq_ignore_ascii_case("Expires") {
+            if let Some(time) = parse_cookie_date(value) {
+                expires = Some(time);
+            }
+        } else if key.eq_ignore_ascii_case("Max-Age") {
+            if let Ok(seconds) = value.parse::<i64>() {
+                max_age = Some(seconds);
+            }
+        } else if key.eq_ignore_ascii_case("Domain") {
+            let domain = value.trim_start_matches('.').to_ascii_lowercase();
+            if domain.is_empty() {
+                continue;
+            }
+            // 公開サフィックスはホストと一致するときだけ、ホスト限定のクッキーとして受け入れる
+            if is_public_suffix(&domain) {
+                if domain != host {
+                    return None;
+                }
+                cookie.domain = host.clone();
+                cookie.host_only = true;
+                continue;
+            }
+            if !domain_match(&host, &domain) {
+                return None;
+            }
+            cookie.domain = domain;
+            cookie.host_only = false;
+        } else if key.eq_ignore_ascii_case("Path") {
+            if value.starts_with('/') {
+                cookie.path = value.to_string();
+            }
+        } else if key.eq_ignore_ascii_case("Secure") {
+            cookie.secure = true;
+        } else if key.eq_ignore_ascii_case("HttpOnly") {
+            cookie.http_only = true;
+        }
+    }
+
+    // Max-Age は Expires より優先される
+    cookie.expires = match max_age {
+        Some(seconds) => Some(now.saturating_add(seconds)),
+        None => expires,
+    };
+
+    Some(cookie)
+}
+
+/// Public Suffix List を持っていないため、1 ラベルのドメインと主な 2 ラベルの
+/// 公開サフィックスだけを公開サフィックスとして扱う
+/// https://publicsuffix.org/
+fn is_public_suffix(domain: &str) -> bool {
+    const SECOND_LEVEL_SUFFIXES: [&str; 8] = ["ac", "co", "com", "ed", "go", "gov", "ne", "or"];
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    match labels.as_slice() {
+        [_] => true,
+        [second, top] => top.len() == 2 && SECOND_LEVEL_SUFFIXES.contains(second),
+        _ => false,
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc6265#section-5.1.3
+fn domain_match(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+    host.ends_with(domain) && host[..host.len() - domain.len()].ends_with('.')
+}
+
+fn request_path(url: &Url) -> String {
+    let mut path = String::from("/");
+    path.push_str(&url.raw_path());
+    path
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc6265#section-5.1.4
+fn default_path(url: &Url) -> String {
+    let path = request_path(url);
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => path[..index].to_string(),
+    }
+}
+
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    if request_path == cookie_path {
+        return true;
+    }
+    request_path.starts_with(cookie_path)
+        && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/'))
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc6265#section-5.1.1
+///
+/// 日付を Unix 時間 (秒) に変換する
+pub fn parse_cookie_date(date: &str) -> Option<i64> {
+    let is_delimiter = |c: char| matches!(c, '\t' | ' '..='/' | ';'..='@' | '['..='`' | '{'..='~');
+
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+
+    for token in date.split(is_delimiter).filter(|t| !t.is_empty()) {
+        if time.is_none() {
+            if let Some(t) = parse_time(token) {
+                time = Some(t);
+                continue;
+            }
+        }
+        if day.is_none() {
+            if let Some(d) = leading_digits(token, 1, 2) {
+                day = Some(d);
+                continue;
+            }
+        }
+        if month.is_none() {
+            if let Some(m) = parse_month(token) {
+                month = Some(m);
+                continue;
+            }
+        }
+        if year.is_none() {
+            if let Some(y) = leading_digits(token, 2, 4) {
+                year = Some(y);
+                continue;
+            }
+        }
+    }
+
+    let (hour, minute, second) = time?;
+    let day = day?;
+    let month = month?;
+    let mut year = year?;
+    if (70..=99).contains(&year) {
+        year += 1900;
+    } else if year <= 69 {
+        year += 2000;
+    }
+
+    if !(1..=31).contains(&day) || year < 1601 || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    Some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+/// 先頭の `min`〜`max` 桁の数字を読む。数字の直後に数字が続く場合は None
+fn leading_digits(token: &str, min: usize, max: usize) -> Option<i64> {
+    let digits = token.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits < min || digits > max {
+        return None;
+    }
+    token[..digits].parse().ok()
+}
+
+fn parse_time(token: &str) -> Option<(i64, i64, i64)> {
+    let mut parts = token.splitn(3, ':');
+    let hour = leading_digits(parts.next()?, 1, 2)?;
+    let minute = leading_digits(parts.next()?, 1, 2)?;
+    let second = leading_digits(parts.next()?, 1, 2)?;
+    Some((hour, minute, second))
+}
+
+fn parse_month(token: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let prefix = token.get(..3)?.to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|m| *m == prefix)
+        .map(|i| i as i64 + 1)
+}
+
+/// 1970-01-01 からの日数
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::new(s.to_string())
+            .parse()
+            .expect("failed to parse url")
+    }
+
+    #[test]
+    fn test_parse_cookie_date() {
+        assert_eq!(
+            parse_cookie_date("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(1445412480)
+        );
+        assert_eq!(
+            parse_cookie_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+            Some(784111777)
+        );
+        assert_eq!(parse_cookie_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_cookie_date("not a date"), None);
+        assert_eq!(parse_cookie_date("Wed, 32 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_store_and_send() {
+        let mut jar = CookieJar::new();
+        let u = url("http://example.com/index.html");
+        jar.store(&u, "id=abc; HttpOnly", 0);
+        jar.store(&u, "lang=ja", 0);
+
+        assert_eq!(
+            jar.cookie_header(&u, 0),
+            Some("id=abc; lang=ja".to_string())
+        );
+        assert!(jar.cookies()[0].http_only());
+        assert_eq!(jar.cookie_header(&url("http://other.example/"), 0), None);
+    }
+
+    #[test]
+    fn test_replace_cookie() {
+        let mut jar = CookieJar::new();
+        let u = url("http://example.com/");
+        jar.store(&u, "id=abc", 0);
+        jar.store(&u, "id=def", 0);
+
+        assert_eq!(jar.cookie_header(&u, 0), Some("id=def".to_string()));
+    }
+
+    #[test]
+    fn test_path() {
+        let mut jar = CookieJar::new();
+        jar.store(&url("http://example.com/docs/a.html"), "a=1", 0);
+        jar.store(&url("http://example.com/"), "b=2; Path=/docs/api", 0);
+
+        assert_eq!(jar.cookies()[0].path(), "/docs");
+        assert_eq!(
+            jar.cookie_header(&url("http://example.com/docs/api/x"), 0),
+            Some("b=2; a=1".to_string())
+        );
+        assert_eq!(
+            jar.cookie_header(&url("http://example.com/docs"), 0),
+            Some("a=1".to_string())
+        );
+        assert_eq!(jar.cookie_header(&url("http://example.com/docsx"), 0), None);
+    }
+
+    #[test]
+    fn test_encoded_path() {
+        let mut jar = CookieJar::new();
+        jar.store(&url("http://example.com/"), "a=1; Path=/a%2Fb", 0);
+        jar.store(&url("http://example.com/a%2Fb/c"), "b=2", 0);
+
+        assert_eq!(jar.cookies()[1].path(), "/a%2Fb");
+        assert_eq!(
+            jar.cookie_header(&url("http://example.com/a%2Fb/c"), 0),
+            Some("a=1; b=2".to_string())
+        );
+        assert_eq!(jar.cookie_header(&url("http://example.com/a/b/c"), 0), None);
+    }
+
+    #[test]
+    fn test_domain() {
+        let mut jar = CookieJar::new();
+        jar.store(
+            &url("http://www.example.com/"),
+            "a=1; Domain=.example.com",
+            0,
+        );
+        jar.store(
+            &url("http://www.example.com/"),
+            "b=2; Domain=other.example",
+            0,
+        );
+
+        assert_eq!(
+            jar.cookie_header(&url("http://sub.example.com/"), 0),
+            Some("a=1".to_string())
+        );
+        assert_eq!(jar.cookie_header(&url("http://notexample.com/"), 0), None);
+        assert_eq!(jar.cookies().len(), 1);
+    }
+
+    #[test]
+    fn test_public_suffix_domain() {
+        let mut jar = CookieJar::new();
+        jar.store(&url("http://www.example.com/"), "a=1; Domain=com", 0);
+        jar.store(&url("http://www.example.co.jp/"), "b=2; Domain=co.jp", 0);
+        assert!(jar.cookies().is_empty());
+
+        // ホストと一致する場合はホスト限定になる
+        jar.store(&url("http://localhost/"), "c=3; Domain=localhost", 0);
+        assert_eq!(
+            jar.cookie_header(&url("http://localhost/"), 0),
+            Some("c=3".to_string())
+        );
+        assert_eq!(jar.cookie_header(&url("http://sub.localhost/"), 0), None);
+    }
+
+    #[test]
+    fn test_expires_and_max_age() {
+        let mut jar = CookieJar::new();
+        let u = url("http://example.com/");
+        jar.store(&u, "a=1; Max-Age=60", 1000);
+        jar.store(&u, "b=2; Expires=Thu, 01 Jan 1970 00:30:00 GMT", 1000);
+        jar.store(
+            &u,
+            "c=3; Max-Age=60; Expires=Thu, 01 Jan 1970 00:00:01 GMT",
+            1000,
+        );
+
+        assert_eq!(
+            jar.cookie_header(&u, 1000),
+            Some("a=1; b=2; c=3".to_string())
+        );
+        assert_eq!(jar.cookie_header(&u, 1060), Some("b=2".to_string()));
+
+        jar.store(&u, "b=; Max-Age=0", 1000);
+        assert_eq!(jar.cookie_header(&u, 1000), Some("a=1; c=3".to_string()));
+
+        jar.remove_expired(2000);
+        assert!(jar.cookies().is_empty());
+    }
+
+    #[test]
+    fn test_secure() {
+        let mut jar = CookieJar::new();
+        jar.store(&url("https://example.com/"), "a=1; Secure", 0);
+
+        assert_eq!(
+            jar.cookie_header(&url("https://example.com/"), 0),
+            Some("a=1".to_string())
+        );
+        assert_eq!(jar.cookie_header(&url("http://example.com/"), 0), None);
+    }
+
+    #[test]
+    fn test_invalid_set_cookie() {
+        let mut jar = CookieJar::new();
+        let u = url("http://example.com/");
+        jar.store(&u, "novalue", 0);
+        jar.store(&u, "=abc", 0);
+
+        assert!(jar.cookies().is_empty());
+    }
+}
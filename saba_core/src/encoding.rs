@@ -0,0 +1,507 @@
+//! https://encoding.spec.whatwg.org/
+
+mod jis0208;
+
+use alloc::string::String;
+use jis0208::JIS0208;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    ShiftJis,
+    EucJp,
+    /// ISO-8859-1 や US-ASCII のラベルもこのエンコーディングとして扱う
+    Windows1252,
+}
+
+impl Encoding {
+    /// https://encoding.spec.whatwg.org/#concept-encoding-get
+    ///
+    /// 対応していないエンコーディングのラベルの場合は None を返す
+    pub fn for_label(label: &str) -> Option<Self> {
+        let label = label
+            .trim_matches(|c| matches!(c, '\t' | '\n' | '\x0c' | '\r' | ' '))
+            .to_ascii_lowercase();
+        match label.as_str() {
+            "unicode-1-1-utf-8" | "unicode11utf8" | "unicode20utf8" | "utf-8" | "utf8"
+            | "x-unicode20utf8" => Some(Self::Utf8),
+            "csshiftjis" | "ms932" | "ms_kanji" | "shift-jis" | "shift_jis" | "sjis"
+            | "windows-31j" | "x-sjis" => Some(Self::ShiftJis),
+            "cseucpkdfmtjapanese" | "euc-jp" | "x-euc-jp" => Some(Self::EucJp),
+            "ansi_x3.4-1968" | "ascii" | "cp1252" | "cp819" | "csisolatin1" | "ibm819"
+            | "iso-8859-1" | "iso-ir-100" | "iso8859-1" | "iso88591" | "iso_8859-1"
+            | "iso_8859-1:1987" | "l1" | "latin1" | "us-ascii" | "windows-1252" | "x-cp1252" => {
+                Some(Self::Windows1252)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::ShiftJis => "Shift_JIS",
+            Self::EucJp => "EUC-JP",
+            Self::Windows1252 => "windows-1252",
+        }
+    }
+
+    /// 不正なバイト列は U+FFFD に置き換えて UTF-8 の文字列にする
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Utf8 => {
+                let bytes = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(bytes);
+                String::from_utf8_lossy(bytes).into_owned()
+            }
+            Self::ShiftJis => decode_shift_jis(bytes),
+            Self::EucJp => decode_euc_jp(bytes),
+            Self::Windows1252 => decode_windows_1252(bytes),
+        }
+    }
+}
+
+/// BOM、Content-Type の charset パラメータ、HTML の <meta> の順にエンコーディングを決める。
+/// どれからも分からない場合は None を返す
+pub fn detect(content_type: Option<&str>, resource: &[u8]) -> Option<Encoding> {
+    if resource.starts_with(&[0xef, 0xbb, 0xbf]) {
+        return Some(Encoding::Utf8);
+    }
+
+    if let Some(encoding) = content_type.and_then(charset_from_content_type) {
+        return Some(encoding);
+    }
+
+    // <meta> を探すのは HTML の場合だけ
+    match content_type.map(crate::mime::essence) {
+        Some(essence) if essence != "text/html" => None,
+        _ => prescan(resource),
+    }
+}
+
+/// Content-Type ヘッダの値から charset パラメータを取り出す
+pub fn charset_from_content_type(content_type: &str) -> Option<Encoding> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        Encoding::for_label(value.trim().trim_matches('"'))
+    })
+}
+
+/// https://html.spec.whatwg.org/multipage/parsing.html#prescan-a-byte-stream-to-determine-its-encoding
+///
+/// 先頭の 1024 バイトから <meta charset> と <meta http-equiv="Content-Type"> を探す
+pub fn prescan(resource: &[u8]) -> Option<Encoding> {
+    let input = &resource[..resource.len().min(1024)];
+    let mut pos = 0;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if rest.starts_with(b"<!--") {
+            pos += match find(&rest[4..], b"-->") {
+                Some(i) => 4 + i + 3,
+                None => return None,
+            };
+        } else if starts_with_ignore_case(rest, b"<meta")
+            && rest.len() > 5
+            && (is_whitespace_byte(rest[5]) || rest[5] == b'/')
+        {
+            pos += 5;
+            let (encoding, consumed) = meta_encoding(&input[pos..]);
+            if encoding.is_some() {
+                return encoding;
+            }
+            pos += consumed;
+        } else if rest.len() > 1 && rest[0] == b'<' && is_tag_start(&rest[1..]) {
+            // 他のタグは属性ごと読み飛ばす
+            pos += 1;
+            while pos < input.len() && !is_whitespace_byte(input[pos]) && input[pos] != b'>' {
+                pos += 1;
+            }
+            while let Some((_, _, consumed)) = attribute(&input[pos..]) {
+                pos += consumed;
+            }
+            pos += 1;
+        } else if rest.starts_with(b"<!") || rest.starts_with(b"</") || rest.starts_with(b"<?") {
+            pos += match find(rest, b">") {
+                Some(i) => i + 1,
+                None => return None,
+            };
+        } else {
+            pos += 1;
+        }
+    }
+    None
+}
+
+/// <meta の後ろの属性を読み、見つかったエンコーディングと読み進めたバイト数を返す
+fn meta_encoding(input: &[u8]) -> (Option<Encoding>, usize) {
+    let mut pos = 0;
+    let mut got_pragma = false;
+    let mut need_pragma = None;
+    let mut charset = None;
+
+    while let Some((name, value, consumed)) = attribute(&input[pos..]) {
+        pos += consumed;
+        match name.as_str() {
+            "http-equiv" if value.eq_ignore_ascii_case("content-type") => got_pragma = true,
+            "content" if charset.is_none() => {
+                if let Some(encoding) = charset_from_meta_content(&value) {
+                    charset = Some(encoding);
+                    need_pragma = Some(true);
+                }
+            }
+            "charset" if charset.is_none() => {
+                charset = Encoding::for_label(&value);
+                need_pragma = Some(false);
+            }
+            _ => {}
+        }
+    }
+
+    let encoding = match need_pragma {
+        Some(true) if !got_pragma => None,
+        None => None,
+        // UTF-16 の <meta> は UTF-8 として扱う規則があるが、UTF-16 には対応していないので不要
+        _ => charset,
+    };
+    (encoding, pos)
+}
+
+/// https://html.spec.whatwg.org/multipage/urls-and-fetching.html#algorithm-for-extracting-a-character-encoding-from-a-meta-element
+fn charset_from_meta_content(content: &str) -> Option<Encoding> {
+    let lower = content.to_ascii_lowercase();
+    let mut pos = 0;
+    loop {
+        pos += lower[pos..].find("charset")? + "charset".len();
+        let rest = lower[pos..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+        if let Some(rest) = rest.strip_prefix('=') {
+            let rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+            let value = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next()?,
+                _ => rest
+                    .split(|c: char| c.is_ascii_whitespace() || c == ';')
+                    .next()
+                    .unwrap_or(""),
+            };
+            return Encoding::for_label(value);
+        }
+    }
+}
+
+/// https://html.spec.whatwg.org/multipage/parsing.html#concept-get-attributes-when-sniffing
+///
+/// 属性を 1 つ読み、小文字にした名前と値、読み進めたバイト数を返す。タグの終わりでは None を返す
+fn attribute(input: &[u8]) -> Option<(String, String, usize)> {
+    let mut pos = 0;
+    while pos < input.len() && (is_whitespace_byte(input[pos]) || input[pos] == b'/') {
+        pos += 1;
+    }
+    if pos >= input.len() || input[pos] == b'>' {
+        return None;
+    }
+
+    let mut name = String::new();
+    while pos < input.len() {
+        let b = input[pos];
+        if b == b'=' && !name.is_empty() {
+            break;
+        }
+        if is_whitespace_byte(b) || b == b'/' || b == b'>' {
+            break;
+        }
+        name.push(b.to_ascii_lowercase() as char);
+        pos += 1;
+    }
+    while pos < input.len() && is_whitespace_byte(input[pos]) {
+        pos += 1;
+    }
+    if pos >= input.len() || input[pos] != b'=' {
+        return Some((name, String::new(), pos));
+    }
+    pos += 1;
+    while pos < input.len() && is_whitespace_byte(input[pos]) {
+        pos += 1;
+    }
+
+    let mut value = String::new();
+    match input.get(pos) {
+        Some(&quote) if quote == b'"' || quote == b'\'' => {
+            pos += 1;
+            while pos < input.len() && input[pos] != quote {
+                value.push(input[pos].to_ascii_lowercase() as char);
+                pos += 1;
+            }
+            pos += 1;
+        }
+        _ => {
+            while pos < input.len() && !is_whitespace_byte(input[pos]) && input[pos] != b'>' {
+                value.push(input[pos].to_ascii_lowercase() as char);
+                pos += 1;
+            }
+        }
+    }
+    Some((name, value, pos.min(input.len())))
+}
+
+fn is_tag_start(input: &[u8]) -> bool {
+    match input {
+        [b'/', b, ..] => b.is_ascii_alphabetic(),
+        [b, ..] => b.is_ascii_alphabetic(),
+        [] => false,
+    }
+}
+
+fn is_whitespace_byte(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+}
+
+fn starts_with_ignore_case(input: &[u8], prefix: &[u8]) -> bool {
+    input.len() >= prefix.len() && input[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+fn find(input: &[u8], pattern: &[u8]) -> Option<usize> {
+    input.windows(pattern.len()).position(|w| w == pattern)
+}
+
+fn jis0208_code_point(pointer: usize) -> Option<char> {
+    match JIS0208.get(pointer) {
+        Some(0) | None => None,
+        Some(c) => char::from_u32(*c as u32),
+    }
+}
+
+/// https://encoding.spec.whatwg.org/#shift_jis-decoder
+fn decode_shift_jis(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    let mut lead = 0u8;
+    for &b in bytes {
+        if lead != 0 {
+            let l = lead as usize;
+            lead = 0;
+            let offset = if b < 0x7f { 0x40 } else { 0x41 };
+            let lead_offset = if l < 0xa0 { 0x81 } else { 0xc1 };
+            let pointer = match b {
+                0x40..=0x7e | 0x80..=0xfc => Some((l - lead_offset) * 188 + b as usize - offset),
+                _ => None,
+            };
+
+            match pointer {
+                // 外字 (EUDC) は私用領域に割り当てる
+                Some(p @ 8836..=10715) => {
+                    result.push(char::from_u32(0xe000 + p as u32 - 8836).unwrap_or('\u{fffd}'))
+                }
+                Some(p) if jis0208_code_point(p).is_some() => {
+                    result.push(jis0208_code_point(p).unwrap_or('\u{fffd}'))
+                }
+                // 2 バイト目が ASCII の場合は、その文字を捨てずに残す
+                _ if b.is_ascii() => {
+                    result.push('\u{fffd}');
+                    result.push(b as char);
+                }
+                _ => result.push('\u{fffd}'),
+            }
+            continue;
+        }
+
+        match b {
+            0x00..=0x80 => result.push(b as char),
+            // 半角カタカナ
+            0xa1..=0xdf => {
+                result.push(char::from_u32(0xff61 + b as u32 - 0xa1).unwrap_or('\u{fffd}'))
+            }
+            0x81..=0x9f | 0xe0..=0xfc => lead = b,
+            _ => result.push('\u{fffd}'),
+        }
+    }
+    if lead != 0 {
+        result.push('\u{fffd}');
+    }
+    result
+}
+
+/// https://encoding.spec.whatwg.org/#euc-jp-decoder
+///
+/// JIS X 0212 (0x8F で始まる 3 バイトの文字) の対応表は持っていないので U+FFFD にする
+fn decode_euc_jp(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    let mut lead = 0u8;
+    let mut jis0212 = false;
+    for &b in bytes {
+        if lead == 0x8e && (0xa1..=0xdf).contains(&b) {
+            lead = 0;
+            result.push(char::from_u32(0xff61 + b as u32 - 0xa1).unwrap_or('\u{fffd}'));
+            continue;
+        }
+        if lead == 0x8f && (0xa1..=0xfe).contains(&b) {
+            jis0212 = true;
+            lead = b;
+            continue;
+        }
+        if lead != 0 {
+            let l = lead;
+            lead = 0;
+            let mut code_point = None;
+            if (0xa1..=0xfe).contains(&l) && (0xa1..=0xfe).contains(&b) && !jis0212 {
+                code_point = jis0208_code_point((l as usize - 0xa1) * 94 + b as usize - 0xa1);
+            }
+            jis0212 = false;
+
+            match code_point {
+                Some(c) => result.push(c),
+                None if b.is_ascii() => {
+                    result.push('\u{fffd}');
+                    result.push(b as char);
+                }
+                None => result.push('\u{fffd}'),
+            }
+            continue;
+        }
+
+        match b {
+            0x00..=0x7f => result.push(b as char),
+            0x8e | 0x8f | 0xa1..=0xfe => lead = b,
+            _ => result.push('\u{fffd}'),
+        }
+    }
+    if lead != 0 {
+        result.push('\u{fffd}');
+    }
+    result
+}
+
+/// https://encoding.spec.whatwg.org/index-windows-1252.txt
+///
+/// 0x80 から 0x9F 以外は ISO-8859-1 と同じく、バイトの値がそのままコードポイントになる
+const WINDOWS_1252_HIGH: [u16; 32] = [
+    0x20ac, 0x0081, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021, 0x02c6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0x008d, 0x017d, 0x008f, 0x0090, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0x009d, 0x017e, 0x0178,
+];
+
+fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9f => {
+                char::from_u32(WINDOWS_1252_HIGH[b as usize - 0x80] as u32).unwrap_or('\u{fffd}')
+            }
+            _ => b as char,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_label() {
+        assert_eq!(Encoding::for_label(" Shift_JIS "), Some(Encoding::ShiftJis));
+        assert_eq!(Encoding::for_label("windows-31j"), Some(Encoding::ShiftJis));
+        assert_eq!(Encoding::for_label("EUC-JP"), Some(Encoding::EucJp));
+        assert_eq!(
+            Encoding::for_label("iso-8859-1"),
+            Some(Encoding::Windows1252)
+        );
+        assert_eq!(Encoding::for_label("utf8"), Some(Encoding::Utf8));
+        assert_eq!(Encoding::for_label("utf-16"), None);
+    }
+
+    #[test]
+    fn test_shift_jis() {
+        // "日本語ｶﾅ" と外字
+        let bytes = [
+            0x93, 0xfa, 0x96, 0x7b, 0x8c, 0xea, 0xb6, 0xc5, 0x61, 0xf0, 0x40,
+        ];
+        assert_eq!(Encoding::ShiftJis.decode(&bytes), "日本語ｶﾅa\u{e000}");
+    }
+
+    #[test]
+    fn test_shift_jis_invalid() {
+        assert_eq!(Encoding::ShiftJis.decode(&[0x93, 0x21]), "\u{fffd}!");
+        assert_eq!(Encoding::ShiftJis.decode(&[0x61, 0x93]), "a\u{fffd}");
+        assert_eq!(Encoding::ShiftJis.decode(&[0xa0]), "\u{fffd}");
+    }
+
+    #[test]
+    fn test_euc_jp() {
+        // "日本語ｶ" と JIS X 0212 の文字
+        let bytes = [
+            0xc6, 0xfc, 0xcb, 0xdc, 0xb8, 0xec, 0x8e, 0xb6, 0x8f, 0xb0, 0xa1, 0x21,
+        ];
+        assert_eq!(Encoding::EucJp.decode(&bytes), "日本語ｶ\u{fffd}!");
+    }
+
+    #[test]
+    fn test_windows_1252() {
+        assert_eq!(
+            Encoding::Windows1252.decode(&[0x63, 0x61, 0x66, 0xe9, 0x80, 0x81]),
+            "café€\u{81}"
+        );
+    }
+
+    #[test]
+    fn test_utf8_bom() {
+        assert_eq!(
+            Encoding::Utf8.decode(&[0xef, 0xbb, 0xbf, 0x61, 0xff]),
+            "a\u{fffd}"
+        );
+    }
+
+    #[test]
+    fn test_charset_from_content_type() {
+        assert_eq!(
+            charset_from_content_type("text/html; charset=\"Shift_JIS\""),
+            Some(Encoding::ShiftJis)
+        );
+        assert_eq!(charset_from_content_type("text/html"), None);
+    }
+
+    #[test]
+    fn test_prescan_meta_charset() {
+        assert_eq!(
+            prescan(
+                b"<!doctype html><html><head><!-- <meta charset=utf-8> --><META CHARSET='euc-jp'>"
+            ),
+            Some(Encoding::EucJp)
+        );
+    }
+
+    #[test]
+    fn test_prescan_http_equiv() {
+        assert_eq!(
+            prescan(b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=Shift_JIS\">"),
+            Some(Encoding::ShiftJis)
+        );
+        // http-equiv がない content は無視する
+        assert_eq!(
+            prescan(b"<meta content=\"text/html; charset=Shift_JIS\">"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prescan_skips_attributes() {
+        assert_eq!(
+            prescan(b"<div title=\"<meta charset=euc-jp>\"></div>"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        let html = b"<meta charset=shift_jis>";
+        assert_eq!(
+            detect(Some("text/html; charset=euc-jp"), html),
+            Some(Encoding::EucJp)
+        );
+        assert_eq!(detect(Some("text/html"), html), Some(Encoding::ShiftJis));
+        assert_eq!(detect(None, html), Some(Encoding::ShiftJis));
+        assert_eq!(detect(Some("text/plain"), html), None);
+        assert_eq!(
+            detect(Some("text/html; charset=euc-jp"), b"\xef\xbb\xbfa"),
+            Some(Encoding::Utf8)
+        );
+    }
+}
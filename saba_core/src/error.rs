@@ -2,8 +2,14 @@ use alloc::string::String;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Error {
+    UrlParse(String),
     Network(String),
+    Http(u32),
+    ParseHtml(String),
+    ParseCss(String),
+    Js(String),
     UnexpectedInput(String),
     InvalidUI(String),
+    OutOfMemory(String),
     Other(String),
 }
@@ -0,0 +1,229 @@
+//! https://html.spec.whatwg.org/multipage/server-sent-events.html#parsing-an-event-stream
+
+use crate::http::Headers;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    event_type: String,
+    data: String,
+    last_event_id: String,
+}
+
+impl Event {
+    pub fn event_type(&self) -> String {
+        self.event_type.clone()
+    }
+
+    pub fn data(&self) -> String {
+        self.data.clone()
+    }
+
+    pub fn last_event_id(&self) -> String {
+        self.last_event_id.clone()
+    }
+}
+
+/// text/event-stream のボディを受信した分だけ渡して、イベントを取り出す
+#[derive(Debug, Clone, Default)]
+pub struct EventStreamParser {
+    /// まだ改行が来ていない行
+    line: Vec<u8>,
+    data: String,
+    event_type: String,
+    /// id フィールドの値。イベントを送出したときに last_event_id に反映する
+    last_event_id_buffer: String,
+    last_event_id: String,
+    /// 再接続までの待ち時間 (ミリ秒)
+    retry: Option<u64>,
+    bom_checked: bool,
+    /// 直前のバイトが CR だった場合、続く LF は同じ改行として読み飛ばす
+    skip_lf: bool,
+}
+
+impl EventStreamParser {
+    pub fn new() -> Self {
+        Self {
+            line: Vec::new(),
+            data: String::new(),
+            event_type: String::new(),
+            last_event_id_buffer: String::new(),
+            last_event_id: String::new(),
+            retry: None,
+            bom_checked: false,
+            skip_lf: false,
+        }
+    }
+
+    /// 受信したバイト列を読み、空行で区切られて完成したイベントを返す
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+        for &b in bytes {
+            if self.skip_lf {
+                self.skip_lf = false;
+                if b == b'\n' {
+                    continue;
+                }
+            }
+
+            match b {
+                b'\r' | b'\n' => {
+                    self.skip_lf = b == b'\r';
+                    let line = core::mem::take(&mut self.line);
+                    if let Some(event) = self.process_line(&line) {
+                        events.push(event);
+                    }
+                }
+                _ => self.line.push(b),
+            }
+        }
+        events
+    }
+
+    /// 接続が切れたときに呼び、途中まで受信したイベントを捨てる。
+    /// 最後のイベント ID と再接続までの待ち時間は残す
+    pub fn discard_pending(&mut self) {
+        self.line.clear();
+        self.data.clear();
+        self.event_type.clear();
+        self.last_event_id_buffer = self.last_event_id.clone();
+        self.bom_checked = false;
+        self.skip_lf = false;
+    }
+
+    pub fn last_event_id(&self) -> String {
+        self.last_event_id.clone()
+    }
+
+    /// サーバーから retry フィールドで指定された再接続までの待ち時間 (ミリ秒)
+    pub fn retry(&self) -> Option<u64> {
+        self.retry
+    }
+
+    /// https://html.spec.whatwg.org/multipage/server-sent-events.html#the-eventsource-interface
+    ///
+    /// 接続 (再接続) のリクエストに付けるヘッダ
+    pub fn request_headers(&self) -> Headers {
+        let mut headers = Headers::new();
+        headers.append("Accept", "text/event-stream");
+        headers.append("Cache-Control", "no-cache");
+        if !self.last_event_id.is_empty() {
+            headers.append("Last-Event-ID", &self.last_event_id);
+        }
+        headers
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<Event> {
+        let mut line = String::from_utf8_lossy(line).into_owned();
+        if !self.bom_checked {
+            self.bom_checked = true;
+            if let Some(stripped) = line.strip_prefix('\u{feff}') {
+                line = stripped.to_string();
+            }
+        }
+
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_str(), ""),
+        };
+        match name {
+            "event" => self.event_type = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.last_event_id_buffer = value.to_string(),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// https://html.spec.whatwg.org/multipage/server-sent-events.html#dispatchMessage
+    fn dispatch(&mut self) -> Option<Event> {
+        self.last_event_id = self.last_event_id_buffer.clone();
+        let event_type = core::mem::take(&mut self.event_type);
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let mut data = core::mem::take(&mut self.data);
+        data.pop();
+        Some(Event {
+            event_type: if event_type.is_empty() {
+                "message".to_string()
+            } else {
+                event_type
+            },
+            data,
+            last_event_id: self.last_event_id.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_events() {
+        let mut parser = EventStreamParser::new();
+        let events =
+            parser.feed(b": comment\ndata: first\ndata:second\n\nevent: add\nid: 7\ndata: 3\n\n");
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event_type(), "message");
+        assert_eq!(events[0].data(), "first\nsecond");
+        assert_eq!(events[0].last_event_id(), "");
+        assert_eq!(events[1].event_type(), "add");
+        assert_eq!(events[1].data(), "3");
+        assert_eq!(events[1].last_event_id(), "7");
+    }
+
+    #[test]
+    fn test_split_across_chunks() {
+        let mut parser = EventStreamParser::new();
+        assert!(parser.feed(b"\xef\xbb\xbfdata: \xe6\x97").is_empty());
+        assert!(parser.feed(b"\xa5\r").is_empty());
+        let events = parser.feed(b"\n\r\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "日");
+    }
+
+    #[test]
+    fn test_empty_data_is_not_dispatched() {
+        let mut parser = EventStreamParser::new();
+        assert!(parser.feed(b"event: ping\n\n").is_empty());
+        let events = parser.feed(b"data\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type(), "message");
+        assert_eq!(events[0].data(), "");
+    }
+
+    #[test]
+    fn test_retry_and_reconnect() {
+        let mut parser = EventStreamParser::new();
+        parser.feed(b"id: 41\ndata: done\n\nretry: 3000\nretry: 1s\nid: 42\ndata: partial");
+
+        // id: 42 のイベントは受信しきっていないので、最後のイベント ID は 41 のまま
+        assert_eq!(parser.retry(), Some(3000));
+        parser.discard_pending();
+        assert!(parser.feed(b"\n\n").is_empty());
+        assert_eq!(parser.last_event_id(), "41");
+
+        let headers = parser.request_headers();
+        assert_eq!(headers.get("Last-Event-ID"), Some("41".to_string()));
+        assert_eq!(headers.get("Accept"), Some("text/event-stream".to_string()));
+    }
+}
@@ -1,10 +1,15 @@
 use crate::alloc::string::ToString;
+use crate::encoding;
 use crate::error::Error;
+use crate::inflate;
+use crate::mime;
+use crate::url::Url;
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     name: String,
     value: String,
@@ -14,54 +19,333 @@ impl Header {
     pub fn new(name: String, value: String) -> Self {
         Self { name, value }
     }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn value(&self) -> String {
+        self.value.clone()
+    }
+}
+
+/// ヘッダのリスト。名前は大文字小文字を区別せずに比較する
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Headers {
+    headers: Vec<Header>,
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        Self {
+            headers: Vec::new(),
+        }
+    }
+
+    /// 同じ名前のヘッダがあっても残したまま追加する
+    pub fn append(&mut self, name: &str, value: &str) {
+        self.headers
+            .push(Header::new(name.to_string(), value.to_string()));
+    }
+
+    /// 同じ名前のヘッダをすべて置き換える
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.remove(name);
+        self.append(name, value);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.headers.retain(|h| !h.name.eq_ignore_ascii_case(name));
+    }
+
+    /// 最初に見つかったヘッダの値を返す
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value.clone())
+    }
+
+    /// Set-Cookie のように複数回現れるヘッダの値をすべて返す
+    pub fn get_all(&self, name: &str) -> Vec<String> {
+        self.headers
+            .iter()
+            .filter(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value.clone())
+            .collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|h| h.name.eq_ignore_ascii_case(name))
+    }
+
+    /// 別のヘッダのリストを後ろに連結する
+    pub fn extend(&mut self, other: Headers) {
+        self.headers.extend(other.headers);
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, Header> {
+        self.headers.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+}
+
+impl IntoIterator for Headers {
+    type Item = Header;
+    type IntoIter = alloc::vec::IntoIter<Header>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.headers.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Headers {
+    type Item = &'a Header;
+    type IntoIter = core::slice::Iter<'a, Header>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.headers.iter()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HttpRequest {
+    method: String,
+    target: String,
+    headers: Headers,
+    body: String,
+}
+
+impl HttpRequest {
+    /// `target` は先頭の "/" を除いたパスとクエリ
+    pub fn new(method: &str, target: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            target: target.trim_start_matches('/').to_string(),
+            headers: Headers::new(),
+            body: String::new(),
+        }
+    }
+
+    pub fn method(&self) -> String {
+        self.method.clone()
+    }
+
+    pub fn target(&self) -> String {
+        self.target.clone()
+    }
+
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    pub fn headers_mut(&mut self) -> &mut Headers {
+        &mut self.headers
+    }
+
+    pub fn body(&self) -> String {
+        self.body.clone()
+    }
+
+    /// ボディと一緒に Content-Type と Content-Length を設定する
+    pub fn set_body(&mut self, content_type: &str, body: String) {
+        self.headers.set("Content-Type", content_type);
+        self.headers.set("Content-Length", &body.len().to_string());
+        self.body = body;
+    }
+
+    /// リクエストラインとヘッダ、ボディを送信する形式の文字列にする
+    pub fn serialize(&self) -> String {
+        let mut request = format!("{} /{} HTTP/1.1\n", self.method, self.target);
+        for h in &self.headers {
+            request.push_str(&h.name);
+            request.push_str(": ");
+            request.push_str(&h.value);
+            request.push('\n');
+        }
+        request.push('\n');
+        request.push_str(&self.body);
+        request
+    }
+}
+
+pub const DEFAULT_USER_AGENT: &str = "SaBA/0.1.0";
+
+/// HttpClient が全てのリクエストに付けるヘッダの設定
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HttpClientConfig {
+    user_agent: String,
+    accept_language: Option<String>,
+    extra_headers: Headers,
+}
+
+impl HttpClientConfig {
+    pub fn new() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            accept_language: None,
+            extra_headers: Headers::new(),
+        }
+    }
+
+    pub fn user_agent(&self) -> String {
+        self.user_agent.clone()
+    }
+
+    pub fn set_user_agent(&mut self, user_agent: String) {
+        self.user_agent = user_agent;
+    }
+
+    pub fn accept_language(&self) -> Option<String> {
+        self.accept_language.clone()
+    }
+
+    pub fn set_accept_language(&mut self, accept_language: Option<String>) {
+        self.accept_language = accept_language;
+    }
+
+    pub fn extra_headers(&self) -> &Headers {
+        &self.extra_headers
+    }
+
+    /// 既定のヘッダと同じ名前のものは、既定の値を置き換える
+    pub fn set_extra_header(&mut self, name: &str, value: &str) {
+        self.extra_headers.set(name, value);
+    }
+
+    /// リクエストに既定のヘッダを設定する
+    pub fn apply(&self, request: &mut HttpRequest) {
+        let headers = request.headers_mut();
+        headers.set("User-Agent", &self.user_agent);
+        headers.set("Accept", "text/html");
+        if let Some(accept_language) = &self.accept_language {
+            headers.set("Accept-Language", accept_language);
+        }
+        headers.set("Accept-Encoding", "gzip, deflate, br");
+        for h in &self.extra_headers {
+            headers.set(&h.name, &h.value);
+        }
+    }
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HttpResponse {
     version: String,
     status_code: u32,
     reason: String,
-    headers: Vec<Header>,
+    headers: Headers,
     body: String,
+    /// Content-Encoding を展開した後、文字コードを変換する前のボディ
+    body_bytes: Vec<u8>,
+    /// 文字コードを変換する前のバイト列から判定した MIME タイプ
+    content_type: String,
+    url: Option<Url>,
 }
 
 impl HttpResponse {
     pub fn new(raw_response: String) -> Result<Self, Error> {
-        let preprocessed_response = raw_response.trim_start().replace("\r\n", "\n");
+        Self::from_bytes(raw_response.as_bytes())
+    }
 
-        let (status_line, remaining) = match preprocessed_response.split_once('\n') {
-            Some((s, r)) => (s, r),
+    /// 圧縮されたボディは UTF-8 として解釈できないので、受信したバイト列のまま受け取る
+    pub fn from_bytes(raw_response: &[u8]) -> Result<Self, Error> {
+        let mut raw_response = raw_response;
+        // 103 Early Hints などの中間レスポンスは読み飛ばす
+        while let Some(len) = interim_response_length(raw_response) {
+            raw_response = &raw_response[len..];
+        }
+        let start = raw_response
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(raw_response.len());
+        let raw_response = &raw_response[start..];
+
+        let (status_line, remaining) = match raw_response.iter().position(|b| *b == b'\n') {
+            Some(i) => (&raw_response[..i], &raw_response[i + 1..]),
             None => {
                 return Err(Error::Network(format!(
                     "invalid http response: {}",
-                    preprocessed_response
+                    String::from_utf8_lossy(raw_response)
                 )))
             }
         };
+        let status_line = String::from_utf8_lossy(status_line);
+        let status_line = status_line.trim_end_matches('\r');
 
-        let (headers, body) = match remaining.split_once("\n\n") {
-            Some((h, b)) => {
-                let mut headers = Vec::new();
-                for header in h.split('\n') {
-                    let splitted_header: Vec<&str> = header.splitn(2, ':').collect();
-                    headers.push(Header::new(
-                        String::from(splitted_header[0].trim()),
-                        String::from(splitted_header[1].trim()),
-                    ));
-                }
-                (headers, b)
-            }
-            None => (Vec::new(), remaining),
+        // ボディ中の改行は chunked のサイズ計算に影響するので、正規化せずにそのまま残す
+        let (mut headers, body) = match split_headers_and_body(remaining) {
+            Some((h, b)) => (parse_headers(&String::from_utf8_lossy(h)), b),
+            None => (Headers::new(), remaining),
         };
 
+        let mut body = body.to_vec();
+        if is_chunked(&headers) {
+            let (decoded, trailers) = decode_chunked(&body)?;
+            body = decoded;
+            headers.extend(trailers);
+        }
         let statuses: Vec<&str> = status_line.split(' ').collect();
+        let status_code = statuses[1].parse().unwrap_or(404);
+
+        // 304 や HEAD へのレスポンスは Content-Encoding があってもボディが空になる
+        let has_body = !body.is_empty()
+            && !(100..200).contains(&status_code)
+            && status_code != 204
+            && status_code != 304;
+        if let Some(encoding) = headers.get("Content-Encoding") {
+            if has_body {
+                body = inflate::decode_content_encoding(&encoding, &body)?;
+            }
+        }
+
+        let supplied = headers.get("Content-Type");
+        let no_sniff = match headers.get("X-Content-Type-Options") {
+            Some(value) => value.trim().eq_ignore_ascii_case("nosniff"),
+            None => false,
+        };
+        let content_type = mime::sniff(supplied.as_deref(), no_sniff, &body);
+
+        // 文字コードが指定されていないテキストは UTF-8 でなければエラーにする。
+        // 画像などのバイナリは body_bytes から読む
+        let text = match encoding::detect(supplied.as_deref(), &body) {
+            Some(encoding) => encoding.decode(&body),
+            None => match core::str::from_utf8(&body) {
+                Ok(text) => text.to_string(),
+                Err(_) if !content_type.starts_with("text/") => {
+                    String::from_utf8_lossy(&body).into_owned()
+                }
+                Err(e) => return Err(Error::Network(format!("Invalid response body: {}", e))),
+            },
+        };
 
         Ok(Self {
             version: statuses[0].to_string(),
-            status_code: statuses[1].parse().unwrap_or(404),
+            status_code,
             reason: statuses[2].to_string(),
             headers,
-            body: body.to_string(),
+            body: text,
+            body_bytes: body,
+            content_type,
+            url: None,
         })
     }
 
@@ -77,28 +361,227 @@ impl HttpResponse {
         self.reason.clone()
     }
 
-    pub fn headers(&self) -> Vec<Header> {
+    pub fn headers(&self) -> Headers {
         self.headers.clone()
     }
 
+    pub fn headers_mut(&mut self) -> &mut Headers {
+        &mut self.headers
+    }
+
     pub fn body(&self) -> String {
         self.body.clone()
     }
 
-    pub fn header_value(&self, name: &str) -> Result<String, String> {
-        for h in &self.headers {
-            if h.name == name {
-                return Ok(h.value.clone());
-            }
+    pub fn body_bytes(&self) -> Vec<u8> {
+        self.body_bytes.clone()
+    }
+
+    /// リダイレクトを辿った後の最終的な URL
+    pub fn url(&self) -> Option<Url> {
+        self.url.clone()
+    }
+
+    pub fn set_url(&mut self, url: Url) {
+        self.url = Some(url);
+    }
+
+    /// 3xx のリダイレクトレスポンスであれば Location ヘッダの値を返す
+    pub fn redirect_location(&self) -> Option<String> {
+        if !matches!(self.status_code, 301 | 302 | 303 | 307 | 308) {
+            return None;
+        }
+
+        self.headers.get("Location")
+    }
+
+    pub fn header_value(&self, name: &str) -> Result<String, Error> {
+        match self.headers.get(name) {
+            Some(value) => Ok(value),
+            None => Err(Error::Other(format!("failed to find {} in headers", name))),
+        }
+    }
+
+    /// ステータスコードが 4xx/5xx のときに Error::Http を返す
+    pub fn error_for_status(self) -> Result<Self, Error> {
+        if self.status_code >= 400 {
+            return Err(Error::Http(self.status_code));
+        }
+        Ok(self)
+    }
+
+    pub fn content_type(&self) -> String {
+        self.content_type.clone()
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc9112#section-6.3
+///
+/// 受信途中のバイト列から、レスポンス全体の長さを求める。まだ受信しきっていない場合と、
+/// Content-Length も chunked もなく接続が閉じられるまでがボディになる場合は None を返す
+pub fn response_length(received: &[u8]) -> Option<usize> {
+    if let Some(len) = interim_response_length(received) {
+        return response_length(&received[len..]).map(|rest| len + rest);
+    }
+
+    let start = received.iter().position(|b| !b.is_ascii_whitespace())?;
+    let status_end = start + received[start..].iter().position(|b| *b == b'\n')? + 1;
+    let (head, body) = split_headers_and_body(&received[status_end..])?;
+    let head_len = received.len() - body.len();
+
+    // 1xx、204、304 のレスポンスはボディを持たない
+    let status_line = String::from_utf8_lossy(&received[start..status_end]);
+    let status_code: u32 = status_line.split(' ').nth(1)?.trim().parse().ok()?;
+    if (100..200).contains(&status_code) || status_code == 204 || status_code == 304 {
+        return Some(head_len);
+    }
+
+    let headers = parse_headers(&String::from_utf8_lossy(head));
+    if is_chunked(&headers) {
+        return chunked_length(body).map(|len| head_len + len);
+    }
+
+    let content_length: usize = headers.get("Content-Length")?.trim().parse().ok()?;
+    if body.len() < content_length {
+        return None;
+    }
+    Some(head_len + content_length)
+}
+
+/// 101 以外の 1xx の中間レスポンスであれば、その長さを返す。最終的なレスポンスはこの後に続く
+fn interim_response_length(received: &[u8]) -> Option<usize> {
+    let start = received.iter().position(|b| !b.is_ascii_whitespace())?;
+    let status_end = start + received[start..].iter().position(|b| *b == b'\n')? + 1;
+    let (_, body) = split_headers_and_body(&received[status_end..])?;
+
+    let status_line = String::from_utf8_lossy(&received[start..status_end]);
+    let status_code: u32 = status_line.split(' ').nth(1)?.trim().parse().ok()?;
+    if (100..200).contains(&status_code) && status_code != 101 {
+        return Some(received.len() - body.len());
+    }
+    None
+}
+
+/// 最初の空行でヘッダ部分とボディを分ける
+fn split_headers_and_body(s: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut line_start = 0;
+    for line in s.split_inclusive(|b| *b == b'\n') {
+        if line == b"\n" || line == b"\r\n" {
+            return Some((&s[..line_start], &s[line_start + line.len()..]));
         }
+        line_start += line.len();
+    }
+    None
+}
+
+fn parse_headers(s: &str) -> Headers {
+    let mut headers = Headers::new();
+    for header in s.lines() {
+        if let Some((name, value)) = header.split_once(':') {
+            headers.append(name.trim(), value.trim());
+        }
+    }
+    headers
+}
+
+fn is_chunked(headers: &Headers) -> bool {
+    headers.get_all("Transfer-Encoding").iter().any(|value| {
+        value
+            .split(',')
+            .any(|coding| coding.trim().eq_ignore_ascii_case("chunked"))
+    })
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc9112#section-7.1
+///
+/// チャンクを連結したボディと、トレーラーのヘッダを返す
+fn decode_chunked(raw: &[u8]) -> Result<(Vec<u8>, Headers), Error> {
+    let invalid = || Error::Network("invalid chunked body".to_string());
+
+    let mut body = Vec::new();
+    let mut pos = 0;
+    loop {
+        let line_end = match raw[pos..].iter().position(|b| *b == b'\n') {
+            Some(i) => pos + i,
+            None => return Err(invalid()),
+        };
+        let line = core::str::from_utf8(&raw[pos..line_end]).map_err(|_| invalid())?;
+        // chunk-ext (";name=value") は無視する
+        let size = match line.split_once(';') {
+            Some((size, _)) => size,
+            None => line,
+        };
+        let size = usize::from_str_radix(size.trim(), 16).map_err(|_| invalid())?;
+        pos = line_end + 1;
+
+        if size == 0 {
+            break;
+        }
+
+        if size > raw.len() - pos {
+            return Err(invalid());
+        }
+        body.extend_from_slice(&raw[pos..pos + size]);
+        pos += size;
+
+        if raw[pos..].starts_with(b"\r\n") {
+            pos += 2;
+        } else if raw[pos..].starts_with(b"\n") {
+            pos += 1;
+        } else {
+            return Err(invalid());
+        }
+    }
+
+    let trailer = match split_headers_and_body(&raw[pos..]) {
+        Some((t, _)) => t,
+        None => &raw[pos..],
+    };
+    let trailers = parse_headers(&String::from_utf8_lossy(trailer));
+
+    Ok((body, trailers))
+}
+
+/// 最後のチャンクとトレーラーまで受信していれば、chunked のボディの長さを返す
+fn chunked_length(raw: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let line_end = pos + raw[pos..].iter().position(|b| *b == b'\n')?;
+        let line = core::str::from_utf8(&raw[pos..line_end]).ok()?;
+        let size = match line.split_once(';') {
+            Some((size, _)) => size,
+            None => line,
+        };
+        let size = usize::from_str_radix(size.trim(), 16).ok()?;
+        pos = line_end + 1;
+
+        if size == 0 {
+            break;
+        }
+
+        // チャンクのデータの後には改行が続く
+        pos = pos.checked_add(size)?;
+        let data_end = pos + raw.get(pos..)?.iter().position(|b| *b == b'\n')?;
+        pos = data_end + 1;
+    }
 
-        Err(format!("failed to find {} in headers", name))
+    // トレーラーは空行で終わる
+    for line in raw[pos..].split_inclusive(|b| *b == b'\n') {
+        if !line.ends_with(b"\n") {
+            return None;
+        }
+        pos += line.len();
+        if line == b"\n" || line == b"\r\n" {
+            return Some(pos);
+        }
     }
+    None
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn test_invalid() {
@@ -150,4 +633,288 @@ mod tests {
 
         assert_eq!(res.body(), "body message".to_string());
     }
+
+    #[test]
+    fn test_crlf() {
+        let raw = "HTTP/1.1 200 OK\r\nDate: xx xx xx\r\n\r\nline1\r\nline2".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.reason(), "OK");
+        assert_eq!(res.header_value("Date"), Ok("xx xx xx".to_string()));
+        assert_eq!(res.body(), "line1\r\nline2".to_string());
+    }
+
+    #[test]
+    fn test_chunked() {
+        let raw = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n7\r\n, world\r\n0\r\n\r\n"
+            .to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "Hello, world".to_string());
+    }
+
+    #[test]
+    fn test_chunked_with_crlf_in_data_and_extension() {
+        let raw = "HTTP/1.1 200 OK\r\ntransfer-encoding: gzip, chunked\r\n\r\n7;name=value\r\na\r\nb\r\nc\r\nA\r\n0123456789\r\n0\r\n\r\n"
+            .to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "a\r\nb\r\nc0123456789".to_string());
+    }
+
+    #[test]
+    fn test_chunked_trailers() {
+        let raw = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nbody\r\n0\r\nExpires: never\r\nX-Checksum: abc\r\n\r\n"
+            .to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "body".to_string());
+        assert_eq!(res.header_value("Expires"), Ok("never".to_string()));
+        assert_eq!(res.header_value("X-Checksum"), Ok("abc".to_string()));
+    }
+
+    #[test]
+    fn test_chunked_invalid() {
+        let raw = "HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\nzz\nbody\n0\n\n".to_string();
+        assert!(HttpResponse::new(raw).is_err());
+
+        let raw = "HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n10\nshort\n".to_string();
+        assert!(HttpResponse::new(raw).is_err());
+
+        let raw = "HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\nffffffffffffffff\nbody\n0\n\n"
+            .to_string();
+        assert!(HttpResponse::new(raw).is_err());
+    }
+
+    #[test]
+    fn test_gzip_body() {
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0xd7, 0x51, 0x48, 0xaf, 0xca, 0x2c, 0x00, 0x00, 0x4a, 0x9b, 0xb1, 0x5c, 0x0b,
+            0x00, 0x00, 0x00,
+        ]);
+        let res = HttpResponse::from_bytes(&raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "hello, gzip".to_string());
+    }
+
+    #[test]
+    fn test_gzip_without_body() {
+        let raw = b"HTTP/1.1 304 Not Modified\r\nContent-Encoding: gzip\r\nETag: \"a\"\r\n\r\n";
+        let res = HttpResponse::from_bytes(raw).expect("failed to parse http response");
+        assert_eq!(res.status_code(), 304);
+        assert_eq!(res.body(), "");
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: 31\r\n\r\n";
+        let res = HttpResponse::from_bytes(raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "");
+    }
+
+    #[test]
+    fn test_chunked_deflate_body() {
+        let mut raw =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nContent-Encoding: deflate\r\n\r\n4\r\n"
+                .to_vec();
+        raw.extend_from_slice(&[0x78, 0x9c, 0xcb, 0x48]);
+        raw.extend_from_slice(b"\r\nf\r\n");
+        raw.extend_from_slice(&[
+            0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0xa8, 0xca, 0xc9, 0x4c, 0x02, 0x00, 0x18, 0xb2, 0x04,
+            0x12,
+        ]);
+        raw.extend_from_slice(b"\r\n0\r\n\r\n");
+        let res = HttpResponse::from_bytes(&raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "hello, zlib".to_string());
+    }
+
+    #[test]
+    fn test_header_not_found() {
+        let raw = "HTTP/1.1 200 OK\nDate: xx xx xx\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert!(matches!(res.header_value("Location"), Err(Error::Other(_))));
+    }
+
+    #[test]
+    fn test_error_for_status() {
+        let raw = "HTTP/1.1 200 OK\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert!(res.error_for_status().is_ok());
+
+        let raw = "HTTP/1.1 404 NotFound\n\nnot found".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.error_for_status().unwrap_err(), Error::Http(404));
+    }
+
+    #[test]
+    fn test_redirect_location() {
+        let raw = "HTTP/1.1 301 MovedPermanently\nlocation: /new\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.redirect_location(), Some("/new".to_string()));
+
+        let raw = "HTTP/1.1 200 OK\nLocation: /new\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.redirect_location(), None);
+
+        let raw = "HTTP/1.1 304 NotModified\nLocation: /new\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.redirect_location(), None);
+    }
+
+    #[test]
+    fn test_content_type() {
+        let raw =
+            "HTTP/1.1 200 OK\ncontent-type: text/html; charset=utf-8\n\n<p>hi</p>".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.content_type(), "text/html");
+    }
+
+    #[test]
+    fn test_content_type_sniffed() {
+        let raw = "HTTP/1.1 200 OK\nDate: xx xx xx\n\n<html><body>hi</body></html>".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.content_type(), "text/html");
+
+        let raw = "HTTP/1.1 200 OK\nX-Content-Type-Options: nosniff\n\n<html></html>".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.content_type(), "text/plain");
+    }
+
+    #[test]
+    fn test_binary_body() {
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Type: image/jpeg\r\n\r\n".to_vec();
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0xff, 0x00];
+        raw.extend_from_slice(&png);
+        let res = HttpResponse::from_bytes(&raw).expect("failed to parse http response");
+        assert_eq!(res.content_type(), "image/png");
+        assert_eq!(res.body_bytes(), png.to_vec());
+
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[0xff, 0xfe, 0x00]);
+        assert!(HttpResponse::from_bytes(&raw).is_err());
+    }
+
+    #[test]
+    fn test_response_length_content_length() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+        assert_eq!(response_length(raw), Some(raw.len()));
+        assert_eq!(response_length(&raw[..raw.len() - 1]), None);
+
+        let mut pipelined = raw.to_vec();
+        pipelined.extend_from_slice(b"HTTP/1.1 200 OK");
+        assert_eq!(response_length(&pipelined), Some(raw.len()));
+    }
+
+    #[test]
+    fn test_response_length_chunked() {
+        let raw = b"HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n5\nhello\n0\nExpires: 0\n\n";
+        assert_eq!(response_length(raw), Some(raw.len()));
+        assert_eq!(response_length(&raw[..raw.len() - 1]), None);
+        assert_eq!(response_length(&raw[..raw.len() - 12]), None);
+    }
+
+    #[test]
+    fn test_response_length_chunked_oversized() {
+        let raw = b"HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\nffffffffffffffff\nhello\n0\n\n";
+        assert_eq!(response_length(raw), None);
+    }
+
+    #[test]
+    fn test_response_length_without_body() {
+        let raw = b"HTTP/1.1 304 NotModified\nContent-Length: 100\n\n";
+        assert_eq!(response_length(raw), Some(raw.len()));
+    }
+
+    #[test]
+    fn test_interim_response() {
+        let raw = b"HTTP/1.1 103 Early Hints\r\nLink: </a.css>\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+        assert_eq!(response_length(&raw[..50]), None);
+        assert_eq!(response_length(raw), Some(raw.len()));
+
+        let res = HttpResponse::from_bytes(raw).expect("failed to parse http response");
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.body(), "ok");
+
+        let raw = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n\x81\x00";
+        assert_eq!(response_length(raw), Some(raw.len() - 2));
+    }
+
+    #[test]
+    fn test_response_length_until_close() {
+        assert_eq!(response_length(b"HTTP/1.1 200 OK\n\nhello"), None);
+        assert_eq!(response_length(b"HTTP/1.1 200 OK\nContent-"), None);
+    }
+
+    #[test]
+    fn test_headers_case_insensitive() {
+        let mut headers = Headers::new();
+        headers.append("Set-Cookie", "a=1");
+        headers.append("set-cookie", "b=2");
+        headers.append("Content-Type", "text/html");
+
+        assert_eq!(headers.get("content-type"), Some("text/html".to_string()));
+        assert_eq!(
+            headers.get_all("SET-COOKIE"),
+            vec!["a=1".to_string(), "b=2".to_string()]
+        );
+
+        headers.set("CONTENT-TYPE", "text/plain");
+        assert_eq!(
+            headers.get_all("Content-Type"),
+            vec!["text/plain".to_string()]
+        );
+
+        headers.remove("Set-Cookie");
+        assert!(!headers.contains("set-cookie"));
+        assert_eq!(headers.len(), 1);
+    }
+
+    #[test]
+    fn test_header_value_case_insensitive() {
+        let res = HttpResponse::new("HTTP/1.1 200 OK\ncontent-length: 0\n\n".to_string())
+            .expect("failed to parse http response");
+        assert_eq!(res.header_value("Content-Length"), Ok("0".to_string()));
+    }
+
+    #[test]
+    fn test_request_serialize() {
+        let mut req = HttpRequest::new("POST", "/form?x=1");
+        req.headers_mut().append("Host", "example.com");
+        req.set_body("application/x-www-form-urlencoded", "a=b".to_string());
+
+        assert_eq!(
+            req.serialize(),
+            "POST /form?x=1 HTTP/1.1\nHost: example.com\nContent-Type: application/x-www-form-urlencoded\nContent-Length: 3\n\na=b"
+        );
+    }
+
+    #[test]
+    fn test_client_config() {
+        let mut config = HttpClientConfig::new();
+        config.set_user_agent("TestAgent/1.0".to_string());
+        config.set_accept_language(Some("ja, en;q=0.8".to_string()));
+        config.set_extra_header("accept", "*/*");
+        config.set_extra_header("DNT", "1");
+
+        let mut req = HttpRequest::new("GET", "/");
+        config.apply(&mut req);
+        let headers = req.headers();
+        assert_eq!(headers.get("User-Agent"), Some("TestAgent/1.0".to_string()));
+        assert_eq!(
+            headers.get("Accept-Language"),
+            Some("ja, en;q=0.8".to_string())
+        );
+        assert_eq!(headers.get_all("Accept"), vec!["*/*".to_string()]);
+        assert_eq!(headers.get("dnt"), Some("1".to_string()));
+    }
+
+    #[test]
+    fn test_shift_jis_body() {
+        let mut raw = b"HTTP/1.1 200 OK\nContent-Type: text/html; charset=Shift_JIS\n\n".to_vec();
+        raw.extend_from_slice(&[0x93, 0xfa, 0x96, 0x7b]);
+        let res = HttpResponse::from_bytes(&raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "日本");
+    }
+
+    #[test]
+    fn test_meta_charset_body() {
+        let mut raw = b"HTTP/1.1 200 OK\n\n<meta charset=euc-jp>".to_vec();
+        raw.extend_from_slice(&[0xc6, 0xfc]);
+        let res = HttpResponse::from_bytes(&raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "<meta charset=euc-jp>日");
+    }
 }
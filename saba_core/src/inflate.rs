@@ -0,0 +1,565 @@
+//! https://datatracker.ietf.org/doc/html/rfc1951
+//! https://datatracker.ietf.org/doc/html/rfc1950
+//! https://datatracker.ietf.org/doc/html/rfc1952
+
+use crate::brotli;
+use crate::error::Error;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+const MAX_BITS: usize = 15;
+const MAX_L_CODES: usize = 286;
+const MAX_D_CODES: usize = 30;
+const FIX_L_CODES: usize = 288;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// 展開後のサイズの上限。小さな圧縮データから巨大な出力を作られるのを防ぐ
+pub const MAX_OUTPUT_SIZE: usize = 64 * 1024 * 1024;
+
+fn invalid(message: &str) -> Error {
+    Error::UnexpectedInput(format!("invalid compressed data: {}", message))
+}
+
+/// 展開結果を書き込む前に領域を確保する。上限を超える場合や確保できない場合はエラーにする
+pub(crate) fn reserve(out: &mut Vec<u8>, additional: usize) -> Result<(), Error> {
+    let size = out.len().saturating_add(additional);
+    if size > MAX_OUTPUT_SIZE || out.try_reserve(additional).is_err() {
+        return Err(Error::OutOfMemory(format!(
+            "Failed to allocate {} bytes for the decompressed data",
+            size
+        )));
+    }
+    Ok(())
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_cnt: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_cnt: 0,
+        }
+    }
+
+    fn bits(&mut self, need: u32) -> Result<u32, Error> {
+        let mut value = self.bit_buf;
+        while self.bit_cnt < need {
+            if self.pos >= self.data.len() {
+                return Err(invalid("unexpected end of data"));
+            }
+            value |= (self.data[self.pos] as u32) << self.bit_cnt;
+            self.pos += 1;
+            self.bit_cnt += 8;
+        }
+        self.bit_buf = value >> need;
+        self.bit_cnt -= need;
+        Ok(value & ((1u32 << need) - 1))
+    }
+
+    /// 非圧縮ブロックはバイト境界から始まるので、残りのビットを捨てる
+    fn align_to_byte(&mut self) {
+        self.bit_buf = 0;
+        self.bit_cnt = 0;
+    }
+}
+
+/// 符号長ごとの個数と、符号順に並べたシンボルで表した正準ハフマン符号
+struct Huffman {
+    count: [u16; MAX_BITS + 1],
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u16]) -> Result<Self, Error> {
+        let mut count = [0u16; MAX_BITS + 1];
+        for len in lengths {
+            count[*len as usize] += 1;
+        }
+
+        let mut left: i32 = 1;
+        for c in count.iter().skip(1) {
+            left <<= 1;
+            left -= *c as i32;
+            if left < 0 {
+                return Err(invalid("over-subscribed huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + count[len];
+        }
+
+        let mut symbol = vec![0u16; lengths.len()];
+        for (sym, len) in lengths.iter().enumerate() {
+            if *len != 0 {
+                symbol[offsets[*len as usize] as usize] = sym as u16;
+                offsets[*len as usize] += 1;
+            }
+        }
+
+        Ok(Self { count, symbol })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, Error> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.count[len] as i32;
+            if code - count < first {
+                return Ok(self.symbol[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(invalid("ran out of huffman codes"))
+    }
+}
+
+fn stored(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), Error> {
+    reader.align_to_byte();
+    let pos = reader.pos;
+    if pos + 4 > reader.data.len() {
+        return Err(invalid("unexpected end of data"));
+    }
+    let len = u16::from_le_bytes([reader.data[pos], reader.data[pos + 1]]) as usize;
+    let nlen = u16::from_le_bytes([reader.data[pos + 2], reader.data[pos + 3]]) as usize;
+    if len != !nlen & 0xffff {
+        return Err(invalid("stored block length mismatch"));
+    }
+    let start = pos + 4;
+    if start + len > reader.data.len() {
+        return Err(invalid("unexpected end of data"));
+    }
+    reserve(out, len)?;
+    out.extend_from_slice(&reader.data[start..start + len]);
+    reader.pos = start + len;
+    Ok(())
+}
+
+fn codes(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    lencode: &Huffman,
+    distcode: &Huffman,
+) -> Result<(), Error> {
+    loop {
+        let symbol = lencode.decode(reader)? as usize;
+        if symbol < 256 {
+            reserve(out, 1)?;
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err(invalid("invalid length symbol"));
+        }
+        let len = LENGTH_BASE[symbol] as usize + reader.bits(LENGTH_EXTRA[symbol])? as usize;
+
+        let symbol = distcode.decode(reader)? as usize;
+        if symbol >= DIST_BASE.len() {
+            return Err(invalid("invalid distance symbol"));
+        }
+        let dist = DIST_BASE[symbol] as usize + reader.bits(DIST_EXTRA[symbol])? as usize;
+        if dist > out.len() {
+            return Err(invalid("distance too far back"));
+        }
+
+        reserve(out, len)?;
+        // 距離より長い一致は自分自身をコピーし続ける必要があるので 1 バイトずつ写す
+        let start = out.len() - dist;
+        for i in 0..len {
+            let b = out[start + i];
+            out.push(b);
+        }
+    }
+}
+
+fn fixed(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), Error> {
+    let mut lengths = [0u16; FIX_L_CODES];
+    for (sym, len) in lengths.iter_mut().enumerate() {
+        *len = match sym {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    let lencode = Huffman::new(&lengths)?;
+    let distcode = Huffman::new(&[5u16; MAX_D_CODES])?;
+
+    codes(reader, out, &lencode, &distcode)
+}
+
+fn dynamic(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), Error> {
+    let nlen = reader.bits(5)? as usize + 257;
+    let ndist = reader.bits(5)? as usize + 1;
+    let ncode = reader.bits(4)? as usize + 4;
+    if nlen > MAX_L_CODES || ndist > MAX_D_CODES {
+        return Err(invalid("too many length or distance codes"));
+    }
+
+    let mut lengths = [0u16; MAX_L_CODES + MAX_D_CODES];
+    for index in CODE_LENGTH_ORDER.iter().take(ncode) {
+        lengths[*index] = reader.bits(3)? as u16;
+    }
+    let lencode = Huffman::new(&lengths[..19])?;
+
+    let mut index = 0;
+    while index < nlen + ndist {
+        let symbol = lencode.decode(reader)?;
+        if symbol < 16 {
+            lengths[index] = symbol;
+            index += 1;
+            continue;
+        }
+
+        let (len, repeat) = match symbol {
+            16 => {
+                if index == 0 {
+                    return Err(invalid("repeat with no previous length"));
+                }
+                (lengths[index - 1], 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if index + repeat > nlen + ndist {
+            return Err(invalid("too many code lengths"));
+        }
+        for length in lengths.iter_mut().skip(index).take(repeat) {
+            *length = len;
+        }
+        index += repeat;
+    }
+
+    if lengths[256] == 0 {
+        return Err(invalid("missing end-of-block code"));
+    }
+
+    let lencode = Huffman::new(&lengths[..nlen])?;
+    let distcode = Huffman::new(&lengths[nlen..nlen + ndist])?;
+
+    codes(reader, out, &lencode, &distcode)
+}
+
+/// DEFLATE で圧縮されたデータを展開し、展開結果と読み進めたバイト数を返す
+fn inflate_with_len(data: &[u8]) -> Result<(Vec<u8>, usize), Error> {
+    let mut reader = BitReader::new(data);
+    let mut out = Vec::new();
+
+    loop {
+        let last = reader.bits(1)?;
+        match reader.bits(2)? {
+            0 => stored(&mut reader, &mut out)?,
+            1 => fixed(&mut reader, &mut out)?,
+            2 => dynamic(&mut reader, &mut out)?,
+            _ => return Err(invalid("invalid block type")),
+        }
+        if last == 1 {
+            break;
+        }
+    }
+
+    Ok((out, reader.pos))
+}
+
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    inflate_with_len(data).map(|(out, _)| out)
+}
+
+/// 先頭の 2 バイトが zlib のヘッダ (CM = 8 で、CMF * 256 + FLG が 31 の倍数) かどうか
+fn is_zlib_header(data: &[u8]) -> bool {
+    match data {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && ((*cmf as u16) << 8 | *flg as u16).rem_euclid(31) == 0,
+        _ => false,
+    }
+}
+
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < 6 {
+        return Err(invalid("zlib stream too short"));
+    }
+    if !is_zlib_header(data) {
+        return Err(invalid("invalid zlib header"));
+    }
+    if data[1] & 0x20 != 0 {
+        return Err(invalid("preset dictionary is not supported"));
+    }
+
+    let (out, len) = inflate_with_len(&data[2..])?;
+    let trailer = &data[2 + len..];
+    if trailer.len() < 4 {
+        return Err(invalid("missing adler-32 checksum"));
+    }
+    let expected = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    if adler32(&out) != expected {
+        return Err(invalid("adler-32 checksum mismatch"));
+    }
+
+    Ok(out)
+}
+
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, Error> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    if data.len() < 18 || data[0] != 0x1f || data[1] != 0x8b || data[2] != 8 {
+        return Err(invalid("invalid gzip header"));
+    }
+    let flags = data[3];
+    let mut pos = 10;
+
+    if flags & FEXTRA != 0 {
+        if pos + 2 > data.len() {
+            return Err(invalid("unexpected end of data"));
+        }
+        pos += 2 + u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            match data.iter().skip(pos).position(|b| *b == 0) {
+                Some(i) => pos += i + 1,
+                None => return Err(invalid("unexpected end of data")),
+            }
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    if pos > data.len() {
+        return Err(invalid("unexpected end of data"));
+    }
+
+    let (out, len) = inflate_with_len(&data[pos..])?;
+    let trailer = &data[pos + len..];
+    if trailer.len() < 8 {
+        return Err(invalid("missing gzip trailer"));
+    }
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc32(&out) != crc || out.len() as u32 != size {
+        return Err(invalid("gzip checksum mismatch"));
+    }
+
+    Ok(out)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffff_ffff;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            if crc & 1 != 0 {
+                crc = (crc >> 1) ^ 0xedb8_8320;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    !crc
+}
+
+/// Content-Encoding の値に従ってボディを展開する
+pub fn decode_content_encoding(encoding: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut data = data.to_vec();
+    // 複数指定されている場合は適用された順に並んでいるので、後ろから戻していく
+    for coding in encoding.rsplit(',') {
+        data = match coding.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => gunzip(&data)?,
+            // "deflate" は本来 zlib 形式だが、生の DEFLATE を送るサーバもある
+            "deflate" if is_zlib_header(&data) => zlib_decompress(&data)?,
+            "deflate" => inflate(&data)?,
+            "br" => brotli::decompress(&data)?,
+            "identity" | "" => data,
+            other => {
+                return Err(Error::UnexpectedInput(format!(
+                    "unsupported content encoding: {}",
+                    other
+                )))
+            }
+        };
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn test_inflate_stored() {
+        let data = [0x01, 0x05, 0x00, 0xfa, 0xff, 0x68, 0x65, 0x6c, 0x6c, 0x6f];
+        assert_eq!(inflate(&data), Ok(b"hello".to_vec()));
+    }
+
+    #[test]
+    fn test_inflate_fixed() {
+        let data = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00];
+        assert_eq!(inflate(&data), Ok(b"hello hello hello".to_vec()));
+    }
+
+    #[test]
+    fn test_inflate_dynamic() {
+        let data = [
+            0x85, 0xd2, 0x39, 0x0a, 0x80, 0x30, 0x14, 0x45, 0xd1, 0xde, 0x55, 0xfc, 0x1d, 0xc4,
+            0xe7, 0x2c, 0x84, 0xec, 0x45, 0x31, 0x0e, 0xe0, 0x10, 0x30, 0x8d, 0xbb, 0x17, 0x14,
+            0x4b, 0xff, 0xab, 0x6f, 0x77, 0xb8, 0x76, 0x8e, 0xdb, 0xea, 0x6c, 0x7f, 0x0c, 0x97,
+            0xb3, 0xc1, 0xad, 0xcb, 0xee, 0x25, 0x95, 0x63, 0x94, 0x38, 0x7b, 0x89, 0xfe, 0x8c,
+            0x12, 0xba, 0xc9, 0x5b, 0x13, 0x5c, 0xf2, 0x65, 0xe8, 0x39, 0xd3, 0x73, 0xae, 0xe7,
+            0x42, 0xcf, 0xa5, 0x9e, 0x2b, 0x3d, 0xd7, 0x7a, 0x6e, 0xf4, 0xdc, 0x12, 0x16, 0xc6,
+            0x46, 0xdc, 0x40, 0xe0, 0x40, 0xe4, 0x40, 0xe8, 0x40, 0xec, 0x40, 0xf0, 0x40, 0xf4,
+            0x40, 0xf8, 0xf0, 0xe7, 0x67, 0xde, 0xf9, 0xcc, 0x73, 0xe2, 0x0d,
+        ];
+
+        let mut expected = String::from("<html><body>");
+        for i in 0..20 {
+            expected.push_str(&format!("<p>line {} of the test page</p>\n", i));
+        }
+        expected.push_str("</body></html>");
+
+        assert_eq!(inflate(&data), Ok(expected.into_bytes()));
+    }
+
+    #[test]
+    fn test_gunzip() {
+        let data = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0xd7, 0x51, 0x48, 0xaf, 0xca, 0x2c, 0x00, 0x00, 0x4a, 0x9b, 0xb1, 0x5c, 0x0b,
+            0x00, 0x00, 0x00,
+        ];
+        assert_eq!(gunzip(&data), Ok(b"hello, gzip".to_vec()));
+
+        let mut corrupted = data;
+        corrupted[23] ^= 0xff;
+        assert!(gunzip(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_zlib_decompress() {
+        let data = [
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0xa8, 0xca, 0xc9, 0x4c, 0x02,
+            0x00, 0x18, 0xb2, 0x04, 0x12,
+        ];
+        assert_eq!(zlib_decompress(&data), Ok(b"hello, zlib".to_vec()));
+        assert_eq!(
+            decode_content_encoding("deflate", &data),
+            Ok(b"hello, zlib".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_raw_deflate_content_encoding() {
+        let data = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00];
+        assert_eq!(
+            decode_content_encoding("deflate", &data),
+            Ok(b"hello hello hello".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_brotli_content_encoding() {
+        let data = [
+            0x1b, 0x10, 0x00, 0xf8, 0x8d, 0x94, 0x6e, 0xde, 0x44, 0x55, 0x86, 0x96, 0x6c, 0x20,
+            0x6f, 0x01, 0x4f, 0x1c, 0x60, 0x1c,
+        ];
+        assert_eq!(
+            decode_content_encoding("br", &data),
+            Ok(b"hello hello hello".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_output_size_limit() {
+        let mut out = vec![0u8; 16];
+        assert!(reserve(&mut out, 16).is_ok());
+        assert!(matches!(
+            reserve(&mut out, MAX_OUTPUT_SIZE - 15),
+            Err(Error::OutOfMemory(_))
+        ));
+    }
+
+    #[test]
+    fn test_deflate_content_encoding_size_limit() {
+        // 固定ハフマン符号で、リテラル 0 の後に長さ 258・距離 1 の一致を上限を超えるまで繰り返す
+        let mut data = vec![0x78, 0x01];
+        let mut bit = 0;
+        let mut push_bits = |value: u32, len: u32| {
+            for i in 0..len {
+                if bit % 8 == 0 {
+                    data.push(0);
+                }
+                *data.last_mut().unwrap() |= ((value >> i & 1) as u8) << (bit % 8);
+                bit += 1;
+            }
+        };
+        push_bits(0b011, 3);
+        push_bits(0b0000_1100, 8);
+        for _ in 0..=MAX_OUTPUT_SIZE / 258 {
+            push_bits(0b1010_0011, 8);
+            push_bits(0, 5);
+        }
+        push_bits(0, 7);
+
+        assert!(matches!(
+            decode_content_encoding("deflate", &data),
+            Err(Error::OutOfMemory(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(inflate(&[0x07]).is_err());
+        assert!(inflate(&[0x01, 0x05, 0x00, 0x00, 0x00]).is_err());
+        assert!(gunzip(b"not gzip at all!!!!").is_err());
+        assert!(decode_content_encoding("zstd", b"").is_err());
+    }
+}
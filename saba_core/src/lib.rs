@@ -2,6 +2,16 @@
 
 extern crate alloc;
 
+pub mod auth;
+pub mod base64;
+pub mod brotli;
+pub mod cache;
+pub mod cookie;
+pub mod encoding;
 pub mod error;
+pub mod event_stream;
 pub mod http;
+pub mod inflate;
+pub mod mime;
 pub mod url;
+pub mod websocket;
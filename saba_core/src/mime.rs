@@ -0,0 +1,266 @@
+//! https://mimesniff.spec.whatwg.org/
+
+use alloc::string::{String, ToString};
+
+/// Content-Type ヘッダの値から、パラメータを取り除いた小文字の MIME タイプ (essence) を取り出す
+pub fn essence(content_type: &str) -> String {
+    let essence = match content_type.split_once(';') {
+        Some((e, _)) => e,
+        None => content_type,
+    };
+    essence.trim().to_ascii_lowercase()
+}
+
+/// https://mimesniff.spec.whatwg.org/#mime-type-sniffing-algorithm
+///
+/// `supplied` は Content-Type ヘッダの値、`no_sniff` は X-Content-Type-Options: nosniff が
+/// 指定されているかどうか
+pub fn sniff(supplied: Option<&str>, no_sniff: bool, resource: &[u8]) -> String {
+    // https://mimesniff.spec.whatwg.org/#supplied-mime-type-detection-algorithm
+    //
+    // 設定ミスで付けられていることが多いこれらの値だけ、バイナリかどうかを確認する
+    let check_for_apache_bug = matches!(
+        supplied,
+        Some(
+            "text/plain"
+                | "text/plain; charset=ISO-8859-1"
+                | "text/plain; charset=iso-8859-1"
+                | "text/plain; charset=UTF-8"
+        )
+    );
+    let supplied = supplied.map(essence);
+
+    match supplied {
+        None => return identify_unknown(resource, !no_sniff).to_string(),
+        Some(ref s) if is_unknown(s) => return identify_unknown(resource, !no_sniff).to_string(),
+        _ => {}
+    }
+
+    let supplied = supplied.unwrap_or_default();
+    if no_sniff {
+        return supplied;
+    }
+
+    if check_for_apache_bug {
+        return identify_text_or_binary(resource).to_string();
+    }
+
+    if supplied.starts_with("image/") {
+        if let Some(image) = match_image(resource) {
+            return image.to_string();
+        }
+    }
+
+    supplied
+}
+
+fn is_unknown(essence: &str) -> bool {
+    essence.is_empty()
+        || essence == "unknown/unknown"
+        || essence == "application/unknown"
+        || essence == "*/*"
+}
+
+/// https://mimesniff.spec.whatwg.org/#rules-for-identifying-an-unknown-mime-type
+fn identify_unknown(resource: &[u8], sniff_scriptable: bool) -> &'static str {
+    if sniff_scriptable {
+        if let Some(t) = match_scriptable(resource) {
+            return t;
+        }
+    }
+
+    if let Some(t) = match_bom(resource) {
+        return t;
+    }
+
+    if let Some(t) = match_image(resource) {
+        return t;
+    }
+
+    if resource.iter().any(|b| is_binary_data_byte(*b)) {
+        "application/octet-stream"
+    } else {
+        "text/plain"
+    }
+}
+
+/// https://mimesniff.spec.whatwg.org/#rules-for-text-or-binary
+fn identify_text_or_binary(resource: &[u8]) -> &'static str {
+    if match_bom(resource).is_some() {
+        return "text/plain";
+    }
+
+    if resource.iter().any(|b| is_binary_data_byte(*b)) {
+        "application/octet-stream"
+    } else {
+        "text/plain"
+    }
+}
+
+fn is_binary_data_byte(b: u8) -> bool {
+    matches!(b, 0x00..=0x08 | 0x0b | 0x0e..=0x1a | 0x1c..=0x1f)
+}
+
+fn is_whitespace_byte(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+}
+
+fn is_tag_terminating_byte(b: u8) -> bool {
+    b == b' ' || b == b'>'
+}
+
+fn match_scriptable(resource: &[u8]) -> Option<&'static str> {
+    let start = resource
+        .iter()
+        .position(|b| !is_whitespace_byte(*b))
+        .unwrap_or(resource.len());
+    let rest = &resource[start..];
+
+    const HTML_PATTERNS: [&[u8]; 17] = [
+        b"<!DOCTYPE HTML",
+        b"<HTML",
+        b"<HEAD",
+        b"<SCRIPT",
+        b"<IFRAME",
+        b"<H1",
+        b"<DIV",
+        b"<FONT",
+        b"<TABLE",
+        b"<A",
+        b"<STYLE",
+        b"<TITLE",
+        b"<B",
+        b"<BODY",
+        b"<BR",
+        b"<P",
+        b"<!--",
+    ];
+
+    for pattern in HTML_PATTERNS {
+        if rest.len() > pattern.len()
+            && rest[..pattern.len()].eq_ignore_ascii_case(pattern)
+            && is_tag_terminating_byte(rest[pattern.len()])
+        {
+            return Some("text/html");
+        }
+    }
+
+    if rest.starts_with(b"<?xml") {
+        return Some("text/xml");
+    }
+
+    if resource.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+
+    None
+}
+
+fn match_bom(resource: &[u8]) -> Option<&'static str> {
+    if resource.starts_with(&[0xfe, 0xff])
+        || resource.starts_with(&[0xff, 0xfe])
+        || resource.starts_with(&[0xef, 0xbb, 0xbf])
+    {
+        return Some("text/plain");
+    }
+    None
+}
+
+/// https://mimesniff.spec.whatwg.org/#matching-an-image-type-pattern
+fn match_image(resource: &[u8]) -> Option<&'static str> {
+    if resource.starts_with(&[0x00, 0x00, 0x01, 0x00])
+        || resource.starts_with(&[0x00, 0x00, 0x02, 0x00])
+    {
+        return Some("image/x-icon");
+    }
+    if resource.starts_with(b"BM") {
+        return Some("image/bmp");
+    }
+    if resource.starts_with(b"GIF87a") || resource.starts_with(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if resource.len() >= 14 && resource.starts_with(b"RIFF") && &resource[8..14] == b"WEBPVP" {
+        return Some("image/webp");
+    }
+    if resource.starts_with(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]) {
+        return Some("image/png");
+    }
+    if resource.starts_with(&[0xff, 0xd8, 0xff]) {
+        return Some("image/jpeg");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_essence() {
+        assert_eq!(essence("Text/HTML; charset=utf-8"), "text/html");
+        assert_eq!(essence(" image/png "), "image/png");
+    }
+
+    #[test]
+    fn test_supplied_type() {
+        assert_eq!(sniff(Some("text/css"), false, b"body {}"), "text/css");
+    }
+
+    #[test]
+    fn test_missing_type_html() {
+        assert_eq!(
+            sniff(None, false, b"\n  <!doctype html><html></html>"),
+            "text/html"
+        );
+        assert_eq!(sniff(Some("*/*"), false, b"<p>hello</p>"), "text/html");
+    }
+
+    #[test]
+    fn test_missing_type_text_and_binary() {
+        assert_eq!(sniff(None, false, b"hello world"), "text/plain");
+        assert_eq!(
+            sniff(None, false, &[0x00, 0x01, 0x02]),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_missing_type_image() {
+        assert_eq!(sniff(None, false, b"GIF89a...."), "image/gif");
+        assert_eq!(
+            sniff(
+                Some("application/unknown"),
+                false,
+                &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]
+            ),
+            "image/png"
+        );
+    }
+
+    #[test]
+    fn test_text_plain_binary_check() {
+        assert_eq!(sniff(Some("text/plain"), false, b"<html>"), "text/plain");
+        assert_eq!(
+            sniff(Some("text/plain"), false, &[0x00, 0x01]),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            sniff(Some("text/plain; charset=UTF-8"), false, &[0x00, 0x01]),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            sniff(Some("text/plain; charset=utf-8"), false, &[0x00, 0x01]),
+            "text/plain"
+        );
+        assert_eq!(
+            sniff(Some("TEXT/PLAIN"), false, &[0x00, 0x01]),
+            "text/plain"
+        );
+    }
+
+    #[test]
+    fn test_no_sniff() {
+        assert_eq!(sniff(None, true, b"<html>"), "text/plain");
+        assert_eq!(sniff(Some("text/plain"), true, &[0x00, 0x01]), "text/plain");
+    }
+}
@@ -0,0 +1,390 @@
+//! https://datatracker.ietf.org/doc/html/rfc6455
+
+use crate::base64;
+use crate::error::Error;
+use crate::http::HttpRequest;
+use crate::http::HttpResponse;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// https://datatracker.ietf.org/doc/html/rfc6455#section-1.3
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// `key` は 16 バイトの乱数を base64 にした Sec-WebSocket-Key
+pub fn handshake_request(host: &str, target: &str, key: &str) -> HttpRequest {
+    let mut request = HttpRequest::new("GET", target);
+    let headers = request.headers_mut();
+    headers.append("Host", host);
+    headers.append("Upgrade", "websocket");
+    headers.append("Connection", "Upgrade");
+    headers.append("Sec-WebSocket-Key", key);
+    headers.append("Sec-WebSocket-Version", "13");
+    request
+}
+
+/// Sec-WebSocket-Key に対してサーバーが返すべき Sec-WebSocket-Accept の値
+pub fn accept_key(key: &str) -> String {
+    base64::encode(&sha1(format!("{}{}", key, ACCEPT_GUID).as_bytes()))
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc6455#section-4.1
+pub fn verify_handshake(response: &HttpResponse, key: &str) -> Result<(), Error> {
+    let headers = response.headers();
+    let has_token = |name: &str, token: &str| {
+        headers.get_all(name).iter().any(|value| {
+            value
+                .split(',')
+                .any(|v| v.trim().eq_ignore_ascii_case(token))
+        })
+    };
+
+    if response.status_code() != 101 {
+        return Err(Error::Network(format!(
+            "WebSocket handshake failed with status {}",
+            response.status_code()
+        )));
+    }
+    if !has_token("Upgrade", "websocket") || !has_token("Connection", "upgrade") {
+        return Err(Error::Network(
+            "WebSocket handshake response is not an upgrade".to_string(),
+        ));
+    }
+    if headers.get("Sec-WebSocket-Accept") != Some(accept_key(key)) {
+        return Err(Error::Network("Invalid Sec-WebSocket-Accept".to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl Opcode {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x0 => Some(Self::Continuation),
+            0x1 => Some(Self::Text),
+            0x2 => Some(Self::Binary),
+            0x8 => Some(Self::Close),
+            0x9 => Some(Self::Ping),
+            0xa => Some(Self::Pong),
+            _ => None,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Continuation => 0x0,
+            Self::Text => 0x1,
+            Self::Binary => 0x2,
+            Self::Close => 0x8,
+            Self::Ping => 0x9,
+            Self::Pong => 0xa,
+        }
+    }
+
+    pub fn is_control(&self) -> bool {
+        matches!(self, Self::Close | Self::Ping | Self::Pong)
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc6455#section-5.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    fin: bool,
+    opcode: Opcode,
+    payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(fin: bool, opcode: Opcode, payload: Vec<u8>) -> Self {
+        Self {
+            fin,
+            opcode,
+            payload,
+        }
+    }
+
+    /// ステータスコードと理由を持つ Close フレームを作る
+    pub fn close(code: u16, reason: &str) -> Self {
+        let mut payload = code.to_be_bytes().to_vec();
+        payload.extend_from_slice(reason.as_bytes());
+        Self::new(true, Opcode::Close, payload)
+    }
+
+    pub fn fin(&self) -> bool {
+        self.fin
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    pub fn payload(&self) -> Vec<u8> {
+        self.payload.clone()
+    }
+
+    /// Close フレームのステータスコードと理由
+    pub fn close_reason(&self) -> Option<(u16, String)> {
+        if self.opcode != Opcode::Close || self.payload.len() < 2 {
+            return None;
+        }
+        let code = u16::from_be_bytes([self.payload[0], self.payload[1]]);
+        let reason = String::from_utf8_lossy(&self.payload[2..]).into_owned();
+        Some((code, reason))
+    }
+
+    /// クライアントから送るフレームは必ずマスクする
+    pub fn encode(&self, mask: [u8; 4]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.payload.len() + 14);
+        let fin = if self.fin { 0x80 } else { 0 };
+        bytes.push(fin | self.opcode.to_u8());
+
+        let len = self.payload.len();
+        if len < 126 {
+            bytes.push(0x80 | len as u8);
+        } else if len <= u16::MAX as usize {
+            bytes.push(0x80 | 126);
+            bytes.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            bytes.push(0x80 | 127);
+            bytes.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+
+        bytes.extend_from_slice(&mask);
+        bytes.extend(
+            self.payload
+                .iter()
+                .enumerate()
+                .map(|(i, b)| b ^ mask[i % 4]),
+        );
+        bytes
+    }
+
+    /// サーバーから受け取ったバイト列の先頭のフレームを読み、フレームと読んだバイト数を返す。
+    /// フレームを受信しきっていない場合は None を返す
+    pub fn decode(input: &[u8]) -> Result<Option<(Self, usize)>, Error> {
+        if input.len() < 2 {
+            return Ok(None);
+        }
+
+        if input[0] & 0x70 != 0 {
+            return Err(Error::Network(
+                "WebSocket frame uses reserved bits".to_string(),
+            ));
+        }
+        let fin = input[0] & 0x80 != 0;
+        let opcode = match Opcode::from_u8(input[0] & 0x0f) {
+            Some(opcode) => opcode,
+            None => {
+                return Err(Error::Network(format!(
+                    "Unknown WebSocket opcode: {}",
+                    input[0] & 0x0f
+                )))
+            }
+        };
+        // サーバーから送られるフレームはマスクしてはいけない
+        if input[1] & 0x80 != 0 {
+            return Err(Error::Network(
+                "WebSocket frame from the server is masked".to_string(),
+            ));
+        }
+
+        let (len, mut pos) = match input[1] & 0x7f {
+            126 => {
+                if input.len() < 4 {
+                    return Ok(None);
+                }
+                (u16::from_be_bytes([input[2], input[3]]) as u64, 4)
+            }
+            127 => {
+                if input.len() < 10 {
+                    return Ok(None);
+                }
+                let mut len = [0u8; 8];
+                len.copy_from_slice(&input[2..10]);
+                (u64::from_be_bytes(len), 10)
+            }
+            len => (len as u64, 2),
+        };
+
+        if opcode.is_control() && (!fin || len > 125) {
+            return Err(Error::Network(
+                "Invalid WebSocket control frame".to_string(),
+            ));
+        }
+
+        let len = match usize::try_from(len) {
+            Ok(len) => len,
+            Err(_) => return Err(Error::Network("WebSocket frame is too large".to_string())),
+        };
+        if input.len() - pos < len {
+            return Ok(None);
+        }
+        let payload = input[pos..pos + len].to_vec();
+        pos += len;
+        Ok(Some((Self::new(fin, opcode, payload), pos)))
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc3174
+fn sha1(input: &[u8]) -> [u8; 20] {
+    let mut message = input.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((input.len() as u64).wrapping_mul(8)).to_be_bytes());
+
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, wi) in w.iter().enumerate() {
+            let (f, k) = match i / 20 {
+                0 => ((b & c) | (!b & d), 0x5a827999),
+                1 => (b ^ c ^ d, 0x6ed9eba1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::fmt::Write;
+
+    #[test]
+    fn test_sha1() {
+        let hex = |d: [u8; 20]| {
+            d.iter().fold(String::new(), |mut hex, b| {
+                let _ = write!(hex, "{:02x}", b);
+                hex
+            })
+        };
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(sha1(b"The quick brown fox jumps over the lazy dog")),
+            "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+        );
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // https://datatracker.ietf.org/doc/html/rfc6455#section-1.3
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_handshake() {
+        let request = handshake_request("example.com", "/chat", "dGhlIHNhbXBsZSBub25jZQ==");
+        assert!(request.serialize().starts_with("GET /chat HTTP/1.1\n"));
+        assert_eq!(
+            request.headers().get("sec-websocket-version"),
+            Some("13".to_string())
+        );
+
+        let response = HttpResponse::new(
+            "HTTP/1.1 101 Switching Protocols\nUpgrade: websocket\nConnection: Upgrade\nSec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\n\n"
+                .to_string(),
+        )
+        .expect("failed to parse http response");
+        assert!(verify_handshake(&response, "dGhlIHNhbXBsZSBub25jZQ==").is_ok());
+        assert!(verify_handshake(&response, "AAAAAAAAAAAAAAAAAAAAAA==").is_err());
+    }
+
+    #[test]
+    fn test_encode_masked_frame() {
+        // https://datatracker.ietf.org/doc/html/rfc6455#section-5.7
+        let frame = Frame::new(true, Opcode::Text, b"Hello".to_vec());
+        assert_eq!(
+            frame.encode([0x37, 0xfa, 0x21, 0x3d]),
+            vec![0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58]
+        );
+    }
+
+    #[test]
+    fn test_encode_extended_length() {
+        let frame = Frame::new(true, Opcode::Binary, vec![0; 256]);
+        let bytes = frame.encode([0; 4]);
+        assert_eq!(&bytes[..4], &[0x82, 0xfe, 0x01, 0x00]);
+        assert_eq!(bytes.len(), 4 + 4 + 256);
+    }
+
+    #[test]
+    fn test_decode() {
+        let bytes = [0x01, 0x03, 0x48, 0x65, 0x6c, 0x80, 0x02, 0x6c, 0x6f];
+        let (first, len) = Frame::decode(&bytes).unwrap().unwrap();
+        assert_eq!(first, Frame::new(false, Opcode::Text, b"Hel".to_vec()));
+        assert_eq!(len, 5);
+
+        let (second, len) = Frame::decode(&bytes[5..]).unwrap().unwrap();
+        assert_eq!(
+            second,
+            Frame::new(true, Opcode::Continuation, b"lo".to_vec())
+        );
+        assert_eq!(len, 4);
+
+        assert_eq!(Frame::decode(&bytes[..4]), Ok(None));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        // マスクされたフレーム
+        assert!(Frame::decode(&[0x81, 0x80, 0, 0, 0, 0]).is_err());
+        // 分割された制御フレーム
+        assert!(Frame::decode(&[0x09, 0x00]).is_err());
+        assert!(Frame::decode(&[0x83, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_close_frame() {
+        let frame = Frame::close(1000, "bye");
+        assert_eq!(frame.close_reason(), Some((1000, "bye".to_string())));
+        let bytes = frame.encode([0; 4]);
+        assert_eq!(&bytes[..2], &[0x88, 0x85]);
+    }
+}